@@ -0,0 +1,75 @@
+//! Compare shsc::List with shsc::compact::List on a numeric workload.
+//!
+//! Run with `cargo run --release --example compact --features compact`.
+
+use shsc::traits::ToStr;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITEMS: usize = 1_000_000;
+const ROUNDS: usize = 10;
+
+fn time<F: FnMut() -> f64>(mut f: F) -> (Duration, f64) {
+    let mut best = Duration::MAX;
+    let mut result = 0.0;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        result = black_box(f());
+        best = best.min(start.elapsed());
+    }
+    (best, result)
+}
+
+fn main() {
+    let mut list = shsc::List::new();
+    for i in 0..ITEMS {
+        if i % 2 == 0 {
+            list.append(shsc::todata!(i as f64 * 0.5));
+        } else {
+            list.append(shsc::todata!(i as i64));
+        }
+    }
+    let compact = shsc::compact::List::from(&list);
+    assert_eq!(compact.to_list().tostr(), list.tostr());
+
+    let data_size = std::mem::size_of::<shsc::Data>();
+    let value_size = std::mem::size_of::<shsc::compact::Value>();
+    println!("items: {}", ITEMS);
+    println!(
+        "memory: shsc::List {} MiB ({} bytes per item), compact::List {} MiB ({} bytes per item)",
+        (data_size * ITEMS) >> 20,
+        data_size,
+        (value_size * ITEMS) >> 20,
+        value_size
+    );
+
+    let (list_time, list_sum) = time(|| {
+        let mut sum = 0.0;
+        for data in list.iter() {
+            sum += match data.as_f64() {
+                Some(value) => value,
+                None => data.as_i64().unwrap_or(0) as f64,
+            };
+        }
+        sum
+    });
+    let (compact_time, compact_sum) = time(|| {
+        let mut sum = 0.0;
+        for value in compact.values() {
+            sum += match value.as_f64() {
+                Some(value) => value,
+                None => value.as_i64().unwrap_or(0) as f64,
+            };
+        }
+        sum
+    });
+    assert_eq!(list_sum, compact_sum);
+    println!(
+        "sum: shsc::List {:?}, compact::List {:?}",
+        list_time, compact_time
+    );
+    println!(
+        "speedup: {:.1}x",
+        list_time.as_secs_f64() / compact_time.as_secs_f64()
+    );
+}
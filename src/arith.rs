@@ -0,0 +1,655 @@
+//! Arithmetic on shsc::Data with numeric promotion.
+//!
+//! Binary operations first promote both operands to a common type:
+//! * if either operand is FLOAT64, the result is FLOAT64
+//! * two unsigned operands give the wider unsigned type, e.g. UINT8 + UINT32 -> UINT32
+//! * two signed operands give the wider signed type, e.g. INT8 + INT32 -> INT32
+//! * a signed and an unsigned operand give the narrowest signed type that holds both,
+//!   capped at INT64, e.g. INT8 + UINT8 -> INT16 and INT8 + UINT32 -> INT64
+//!
+//! Negation keeps signed and FLOAT64 types and turns unsigned types into the signed
+//! type of twice the width, e.g. -UINT16 -> INT32 and -UINT64 -> INT64.
+//!
+//! Integer results that do not fit the promoted type are an ArithError::Overflow for the
+//! checked methods, wrap for the wrapping methods and clamp for the saturating methods.
+//! Integer division or remainder by zero is always an ArithError::DivisionByZero.
+//! BOOL, CHAR, NULL and containers are not numeric and give ArithError::TypeMismatch,
+//! or ArithError::UnsupportedType when negated.
+//! The exception is addition: STRING + STRING, BYTES + BYTES and LIST + LIST concatenate.
+//!
+//! The `+ - * / %` and unary `-` operators behave like the checked methods and panic on error.
+
+use std::{fmt, ops};
+
+/// Error returned by arithmetic on shsc::Data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArithError {
+    /// The operands do not support the operation; holds the operation and operand typenames.
+    TypeMismatch(&'static str, std::string::String, std::string::String),
+    /// The operand of a unary operation is not numeric; holds the operation and typename.
+    UnsupportedType(&'static str, std::string::String),
+    /// The result does not fit the promoted type; holds the operation and that typename.
+    Overflow(&'static str, std::string::String),
+    /// Integer division or remainder by zero.
+    DivisionByZero,
+}
+
+impl fmt::Display for ArithError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArithError::TypeMismatch(op, lhs, rhs) => {
+                write!(
+                    f,
+                    "shsc::Data: {}: unsupported operand types {} and {}",
+                    op, lhs, rhs
+                )
+            }
+            ArithError::UnsupportedType(op, typename) => {
+                write!(
+                    f,
+                    "shsc::Data: {}: unsupported operand type {}",
+                    op, typename
+                )
+            }
+            ArithError::Overflow(op, typename) => {
+                write!(f, "shsc::Data: {}: result overflows {}", op, typename)
+            }
+            ArithError::DivisionByZero => write!(f, "shsc::Data: division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for ArithError {}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Op {
+    fn name(self) -> &'static str {
+        match self {
+            Op::Add => "add",
+            Op::Sub => "sub",
+            Op::Mul => "mul",
+            Op::Div => "div",
+            Op::Rem => "rem",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Checked,
+    Wrapping,
+    Saturating,
+}
+
+/// Numeric kind of an operand: signedness and width in bits, or float.
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Unsigned(u32),
+    Signed(u32),
+    Float,
+}
+
+impl Kind {
+    fn of(data: &crate::Data) -> Option<(Kind, i128)> {
+        match data.data {
+            crate::DataTypes::UINT8(v) => Some((Kind::Unsigned(8), v as i128)),
+            crate::DataTypes::UINT16(v) => Some((Kind::Unsigned(16), v as i128)),
+            crate::DataTypes::UINT32(v) => Some((Kind::Unsigned(32), v as i128)),
+            crate::DataTypes::UINT64(v) => Some((Kind::Unsigned(64), v as i128)),
+            crate::DataTypes::INT8(v) => Some((Kind::Signed(8), v as i128)),
+            crate::DataTypes::INT16(v) => Some((Kind::Signed(16), v as i128)),
+            crate::DataTypes::INT32(v) => Some((Kind::Signed(32), v as i128)),
+            crate::DataTypes::INT64(v) => Some((Kind::Signed(64), v as i128)),
+            crate::DataTypes::FLOAT64(_) => Some((Kind::Float, 0)),
+            _ => None,
+        }
+    }
+
+    fn promote(self, other: Kind) -> Kind {
+        match (self, other) {
+            (Kind::Float, _) | (_, Kind::Float) => Kind::Float,
+            (Kind::Unsigned(a), Kind::Unsigned(b)) => Kind::Unsigned(a.max(b)),
+            (Kind::Signed(a), Kind::Signed(b)) => Kind::Signed(a.max(b)),
+            (Kind::Signed(s), Kind::Unsigned(u)) | (Kind::Unsigned(u), Kind::Signed(s)) => {
+                Kind::Signed(s.max(2 * u).min(64))
+            }
+        }
+    }
+
+    fn range(self) -> (i128, i128) {
+        match self {
+            Kind::Unsigned(bits) => (0, (1i128 << bits) - 1),
+            Kind::Signed(bits) => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+            Kind::Float => (i128::MIN, i128::MAX),
+        }
+    }
+
+    fn typename(self) -> std::string::String {
+        self.wrap(0).typename()
+    }
+
+    /// Truncate an integer to this kind, wrapping around like an `as` cast.
+    fn wrap(self, value: i128) -> crate::Data {
+        match self {
+            Kind::Unsigned(8) => crate::todata!(value as u8),
+            Kind::Unsigned(16) => crate::todata!(value as u16),
+            Kind::Unsigned(32) => crate::todata!(value as u32),
+            Kind::Unsigned(_) => crate::todata!(value as u64),
+            Kind::Signed(8) => crate::todata!(value as i8),
+            Kind::Signed(16) => crate::todata!(value as i16),
+            Kind::Signed(32) => crate::todata!(value as i32),
+            Kind::Signed(_) => crate::todata!(value as i64),
+            Kind::Float => crate::todata!(value as f64),
+        }
+    }
+
+    /// Fit an exact integer result into this kind according to mode.
+    /// None means the exact result did not even fit in i128; sign gives its direction.
+    fn fit(
+        self,
+        value: Option<i128>,
+        sign: bool,
+        op: &'static str,
+        mode: Mode,
+    ) -> Result<crate::Data, ArithError> {
+        let (min, max) = self.range();
+        match (value, mode) {
+            (Some(v), _) if v >= min && v <= max => Ok(self.wrap(v)),
+            (_, Mode::Checked) => Err(ArithError::Overflow(op, self.typename())),
+            (Some(v), Mode::Wrapping) => Ok(self.wrap(v)),
+            (Some(v), Mode::Saturating) => Ok(self.wrap(v.clamp(min, max))),
+            (None, Mode::Saturating) => Ok(self.wrap(if sign { min } else { max })),
+            (None, Mode::Wrapping) => unreachable!("shsc::Data: wrapping result computed exactly"),
+        }
+    }
+}
+
+fn to_f64(data: &crate::Data) -> f64 {
+    match data.data {
+        crate::DataTypes::UINT8(v) => v as f64,
+        crate::DataTypes::UINT16(v) => v as f64,
+        crate::DataTypes::UINT32(v) => v as f64,
+        crate::DataTypes::UINT64(v) => v as f64,
+        crate::DataTypes::INT8(v) => v as f64,
+        crate::DataTypes::INT16(v) => v as f64,
+        crate::DataTypes::INT32(v) => v as f64,
+        crate::DataTypes::INT64(v) => v as f64,
+        crate::DataTypes::FLOAT64(v) => v,
+        _ => unreachable!("shsc::Data: to_f64 called on non numeric type"),
+    }
+}
+
+fn binary(
+    lhs: &crate::Data,
+    rhs: &crate::Data,
+    op: Op,
+    mode: Mode,
+) -> Result<crate::Data, ArithError> {
+    let mismatch = || ArithError::TypeMismatch(op.name(), lhs.typename(), rhs.typename());
+    if op == Op::Add {
+        match (&lhs.data, &rhs.data) {
+            (crate::DataTypes::STRING(a), crate::DataTypes::STRING(b)) => {
+                return Ok(crate::todata!(a.concat(b)));
+            }
+            (crate::DataTypes::BYTES(a), crate::DataTypes::BYTES(b)) => {
+                return Ok(crate::todata!(a.concat(b)));
+            }
+            (crate::DataTypes::LIST(a), crate::DataTypes::LIST(b)) => {
+                return Ok(crate::todata!(a.concat(b)));
+            }
+            _ => (),
+        }
+    }
+    let (lkind, a) = Kind::of(lhs).ok_or_else(mismatch)?;
+    let (rkind, b) = Kind::of(rhs).ok_or_else(mismatch)?;
+    let kind = lkind.promote(rkind);
+    if kind == Kind::Float {
+        let (a, b) = (to_f64(lhs), to_f64(rhs));
+        let value = match op {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+            Op::Rem => a % b,
+        };
+        return Ok(crate::todata!(value));
+    }
+    // operands are at most 64 bits wide, so only mul can overflow i128
+    let value = match op {
+        Op::Add => Some(a + b),
+        Op::Sub => Some(a - b),
+        Op::Mul if mode == Mode::Wrapping => Some(a.wrapping_mul(b)),
+        Op::Mul => a.checked_mul(b),
+        Op::Div | Op::Rem if b == 0 => return Err(ArithError::DivisionByZero),
+        Op::Div => Some(a / b),
+        Op::Rem => Some(a % b),
+    };
+    kind.fit(value, (a < 0) != (b < 0), op.name(), mode)
+}
+
+fn negate(data: &crate::Data, mode: Mode) -> Result<crate::Data, ArithError> {
+    let (kind, value) =
+        Kind::of(data).ok_or_else(|| ArithError::UnsupportedType("neg", data.typename()))?;
+    match kind {
+        Kind::Float => Ok(crate::todata!(-to_f64(data))),
+        Kind::Unsigned(bits) => {
+            Kind::Signed((2 * bits).min(64)).fit(Some(-value), true, "neg", mode)
+        }
+        Kind::Signed(_) => kind.fit(Some(-value), true, "neg", mode),
+    }
+}
+
+impl crate::Data {
+    /// Add `other` after numeric promotion, or concatenate two STRINGs, BYTES or LISTs.
+    /// Overflow is an error.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!(1i8).checked_add(&shsc::todata!(2u32)).unwrap().typename(), "INT64");
+    /// assert!(shsc::todata!(255u8).checked_add(&shsc::todata!(1u8)).is_err());
+    /// assert!(shsc::todata!(true).checked_add(&shsc::todata!(1)).is_err());
+    /// ```
+
+    pub fn checked_add(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Add, Mode::Checked)
+    }
+
+    /// Subtract `other` after numeric promotion.
+    /// Overflow is an error.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!(1u8).checked_sub(&shsc::todata!(2i8)).unwrap().typename(), "INT16");
+    /// assert!(shsc::todata!(1u8).checked_sub(&shsc::todata!(2u8)).is_err());
+    /// ```
+
+    pub fn checked_sub(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Sub, Mode::Checked)
+    }
+
+    /// Multiply by `other` after numeric promotion.
+    /// Overflow is an error.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(3).checked_mul(&shsc::todata!(0.5)).unwrap().tostr(), "1.5");
+    /// assert!(shsc::todata!(u64::MAX).checked_mul(&shsc::todata!(u64::MAX)).is_err());
+    /// ```
+
+    pub fn checked_mul(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Mul, Mode::Checked)
+    }
+
+    /// Divide by `other` after numeric promotion.
+    /// Overflow is an error.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(7).checked_div(&shsc::todata!(2)).unwrap().tostr(), "3");
+    /// assert_eq!(
+    ///     shsc::todata!(1).checked_div(&shsc::todata!(0)).err(),
+    ///     Some(shsc::arith::ArithError::DivisionByZero)
+    /// );
+    /// ```
+
+    pub fn checked_div(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Div, Mode::Checked)
+    }
+
+    /// Take the remainder of dividing by `other` after numeric promotion.
+    /// Overflow is an error.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(-7).checked_rem(&shsc::todata!(3)).unwrap().tostr(), "-1");
+    /// assert!(shsc::todata!(7).checked_rem(&shsc::todata!(0u8)).is_err());
+    /// ```
+
+    pub fn checked_rem(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Rem, Mode::Checked)
+    }
+
+    /// Add `other` after numeric promotion, or concatenate two STRINGs, BYTES or LISTs.
+    /// Overflow wraps around at the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(255u8).wrapping_add(&shsc::todata!(1u8)).unwrap().tostr(), "0");
+    /// ```
+
+    pub fn wrapping_add(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Add, Mode::Wrapping)
+    }
+
+    /// Subtract `other` after numeric promotion.
+    /// Overflow wraps around at the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(0u8).wrapping_sub(&shsc::todata!(1u8)).unwrap().tostr(), "255");
+    /// ```
+
+    pub fn wrapping_sub(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Sub, Mode::Wrapping)
+    }
+
+    /// Multiply by `other` after numeric promotion.
+    /// Overflow wraps around at the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(16u8).wrapping_mul(&shsc::todata!(17u8)).unwrap().tostr(), "16");
+    /// ```
+
+    pub fn wrapping_mul(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Mul, Mode::Wrapping)
+    }
+
+    /// Divide by `other` after numeric promotion.
+    /// Overflow wraps around at the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let d = shsc::todata!(i8::MIN).wrapping_div(&shsc::todata!(-1i8)).unwrap();
+    /// assert_eq!(d.tostr(), "-128");
+    /// ```
+
+    pub fn wrapping_div(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Div, Mode::Wrapping)
+    }
+
+    /// Take the remainder of dividing by `other` after numeric promotion.
+    /// Overflow wraps around at the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(7u8).wrapping_rem(&shsc::todata!(4u8)).unwrap().tostr(), "3");
+    /// ```
+
+    pub fn wrapping_rem(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Rem, Mode::Wrapping)
+    }
+
+    /// Add `other` after numeric promotion, or concatenate two STRINGs, BYTES or LISTs.
+    /// Overflow clamps to the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(250u8).saturating_add(&shsc::todata!(10u8)).unwrap().tostr(), "255");
+    /// ```
+
+    pub fn saturating_add(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Add, Mode::Saturating)
+    }
+
+    /// Subtract `other` after numeric promotion.
+    /// Overflow clamps to the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(0u8).saturating_sub(&shsc::todata!(1u8)).unwrap().tostr(), "0");
+    /// ```
+
+    pub fn saturating_sub(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Sub, Mode::Saturating)
+    }
+
+    /// Multiply by `other` after numeric promotion.
+    /// Overflow clamps to the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let d = shsc::todata!(i64::MAX).saturating_mul(&shsc::todata!(-i64::MAX)).unwrap();
+    /// assert_eq!(d.tostr(), i64::MIN.to_string());
+    /// ```
+
+    pub fn saturating_mul(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Mul, Mode::Saturating)
+    }
+
+    /// Divide by `other` after numeric promotion.
+    /// Overflow clamps to the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let d = shsc::todata!(i8::MIN).saturating_div(&shsc::todata!(-1i8)).unwrap();
+    /// assert_eq!(d.tostr(), "127");
+    /// ```
+
+    pub fn saturating_div(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Div, Mode::Saturating)
+    }
+
+    /// Take the remainder of dividing by `other` after numeric promotion.
+    /// Overflow clamps to the bounds of the promoted type.
+    /// ### Arguments
+    /// * `other` - The right hand operand
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(7.5).saturating_rem(&shsc::todata!(2)).unwrap().tostr(), "1.5");
+    /// ```
+
+    pub fn saturating_rem(&self, other: &crate::Data) -> Result<crate::Data, ArithError> {
+        binary(self, other, Op::Rem, Mode::Saturating)
+    }
+
+    /// Negate the value; unsigned types become the signed type of twice the width.
+    /// Overflow is an error.
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!(5u8).checked_neg().unwrap().typename(), "INT16");
+    /// assert!(shsc::todata!(i8::MIN).checked_neg().is_err());
+    /// ```
+
+    pub fn checked_neg(&self) -> Result<crate::Data, ArithError> {
+        negate(self, Mode::Checked)
+    }
+
+    /// Negate the value; unsigned types become the signed type of twice the width.
+    /// Overflow wraps around at the bounds of the promoted type.
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(i8::MIN).wrapping_neg().unwrap().tostr(), "-128");
+    /// ```
+
+    pub fn wrapping_neg(&self) -> Result<crate::Data, ArithError> {
+        negate(self, Mode::Wrapping)
+    }
+
+    /// Negate the value; unsigned types become the signed type of twice the width.
+    /// Overflow clamps to the bounds of the promoted type.
+    /// ### Returns
+    /// The result, or an ArithError
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::todata!(i8::MIN).saturating_neg().unwrap().tostr(), "127");
+    /// ```
+
+    pub fn saturating_neg(&self) -> Result<crate::Data, ArithError> {
+        negate(self, Mode::Saturating)
+    }
+}
+
+impl ops::Add<&crate::Data> for &crate::Data {
+    type Output = crate::Data;
+
+    /// Implement the Add trait for shsc::Data.
+    /// Behaves like checked_add and panics on error.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let sum = &shsc::todata!(1i8) + &shsc::todata!(2u32);
+    /// assert_eq!(sum.typename(), "INT64");
+    /// assert_eq!((shsc::todata!("ab") + shsc::todata!("c")).tostr(), "abc");
+    /// assert_eq!((shsc::todata!(1) - shsc::todata!(0.5)).tostr(), "0.5");
+    /// assert_eq!((-shsc::todata!(2u8)).tostr(), "-2");
+    /// ```
+
+    fn add(self, other: &crate::Data) -> crate::Data {
+        self.checked_add(other)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl ops::Add for crate::Data {
+    type Output = crate::Data;
+
+    fn add(self, other: crate::Data) -> crate::Data {
+        &self + &other
+    }
+}
+
+impl ops::Sub<&crate::Data> for &crate::Data {
+    type Output = crate::Data;
+
+    fn sub(self, other: &crate::Data) -> crate::Data {
+        self.checked_sub(other)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl ops::Sub for crate::Data {
+    type Output = crate::Data;
+
+    fn sub(self, other: crate::Data) -> crate::Data {
+        &self - &other
+    }
+}
+
+impl ops::Mul<&crate::Data> for &crate::Data {
+    type Output = crate::Data;
+
+    fn mul(self, other: &crate::Data) -> crate::Data {
+        self.checked_mul(other)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl ops::Mul for crate::Data {
+    type Output = crate::Data;
+
+    fn mul(self, other: crate::Data) -> crate::Data {
+        &self * &other
+    }
+}
+
+impl ops::Div<&crate::Data> for &crate::Data {
+    type Output = crate::Data;
+
+    fn div(self, other: &crate::Data) -> crate::Data {
+        self.checked_div(other)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl ops::Div for crate::Data {
+    type Output = crate::Data;
+
+    fn div(self, other: crate::Data) -> crate::Data {
+        &self / &other
+    }
+}
+
+impl ops::Rem<&crate::Data> for &crate::Data {
+    type Output = crate::Data;
+
+    fn rem(self, other: &crate::Data) -> crate::Data {
+        self.checked_rem(other)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl ops::Rem for crate::Data {
+    type Output = crate::Data;
+
+    fn rem(self, other: crate::Data) -> crate::Data {
+        &self % &other
+    }
+}
+
+impl ops::Neg for &crate::Data {
+    type Output = crate::Data;
+
+    fn neg(self) -> crate::Data {
+        self.checked_neg().unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl ops::Neg for crate::Data {
+    type Output = crate::Data;
+
+    fn neg(self) -> crate::Data {
+        -&self
+    }
+}
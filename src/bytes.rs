@@ -0,0 +1,395 @@
+use crate::traits::{self, RefC};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const BASE64_DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub struct Bytes {
+    buf: *mut Vec<u8>,
+    refc: *mut i64,
+}
+
+impl Bytes {
+    /// Create a new, empty shsc::Bytes struct.
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::new();
+    /// assert_eq!(b.len(), 0);
+    /// ```
+
+    pub fn new() -> Self {
+        let bytes = Bytes {
+            buf: Box::into_raw(Box::new(Vec::new())),
+            refc: Box::into_raw(Box::new(1)),
+        };
+        bytes
+    }
+
+    /// Create a new shsc::Bytes struct holding a copy of a byte slice.
+    /// ### Arguments
+    /// * `value` - A native byte slice
+    /// ### Returns
+    /// A new Bytes struct
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(b"hello");
+    /// assert_eq!(b.len(), 5);
+    /// ```
+
+    pub fn from(value: &[u8]) -> Self {
+        Bytes::from_vec(value.to_vec())
+    }
+
+    /// Create a new shsc::Bytes struct taking ownership of a byte vector.
+    /// ### Arguments
+    /// * `value` - A native byte vector
+    /// ### Returns
+    /// A new Bytes struct
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from_vec(vec![1, 2, 3]);
+    /// assert_eq!(b.as_slice(), &[1, 2, 3]);
+    /// ```
+
+    pub fn from_vec(value: Vec<u8>) -> Self {
+        Bytes {
+            buf: Box::into_raw(Box::new(value)),
+            refc: Box::into_raw(Box::new(1)),
+        }
+    }
+
+    /// Get the number of bytes.
+    /// ### Returns
+    /// The number of bytes
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(&[1, 2, 3]);
+    /// assert_eq!(b.len(), 3);
+    /// ```
+
+    pub fn len(&self) -> usize {
+        unsafe { (*self.buf).len() }
+    }
+
+    /// Get the byte at index.
+    /// ### Arguments
+    /// * `index` - Index of the byte
+    /// ### Returns
+    /// The byte at index, or None if out of bounds
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(&[1, 2, 3]);
+    /// assert_eq!(b.get(1), Some(2));
+    /// assert_eq!(b.get(3), None);
+    /// ```
+
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.as_slice().get(index).copied()
+    }
+
+    /// Get the bytes as a native slice.
+    /// ### Returns
+    /// A native byte slice
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(b"abc");
+    /// assert_eq!(b.as_slice(), b"abc");
+    /// ```
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { (*self.buf).as_slice() }
+    }
+
+    /// Get the bytes as a mutable native slice.
+    /// Changes are seen by every reference copy.
+    /// ### Returns
+    /// A mutable native byte slice
+    /// ### Example
+    /// ```
+    /// let mut b = shsc::Bytes::from(b"abc");
+    /// b.as_mut_slice()[0] = b'x';
+    /// assert_eq!(b.as_slice(), b"xbc");
+    /// ```
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { (*self.buf).as_mut_slice() }
+    }
+
+    /// Copy a range of bytes into a new shsc::Bytes struct.
+    /// ### Arguments
+    /// * `range` - Range of byte indices
+    /// ### Returns
+    /// A new Bytes struct holding the range
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(b"hello world");
+    /// assert_eq!(b.slice(6..).as_slice(), b"world");
+    /// assert_eq!(b.slice(..5).as_slice(), b"hello");
+    /// ```
+
+    pub fn slice<R>(&self, range: R) -> Bytes
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            ops::Bound::Included(&start) => start,
+            ops::Bound::Excluded(&start) => start + 1,
+            ops::Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            ops::Bound::Included(&end) => end + 1,
+            ops::Bound::Excluded(&end) => end,
+            ops::Bound::Unbounded => self.len(),
+        };
+        if start > end || end > self.len() {
+            panic!(
+                "shsc::Bytes: slice: range {}..{} out of bounds for length {}",
+                start,
+                end,
+                self.len()
+            );
+        }
+        Bytes::from(&self.as_slice()[start..end])
+    }
+
+    /// Concatenate two shsc::Bytes structs.
+    /// ### Arguments
+    /// * `other` - A shsc::Bytes struct
+    /// ### Returns
+    /// A new shsc::Bytes struct
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(b"ab");
+    /// let b2 = shsc::Bytes::from(b"cd");
+    /// assert_eq!(b.concat(&b2).as_slice(), b"abcd");
+    /// ```
+
+    pub fn concat(&self, other: &Bytes) -> Bytes {
+        let mut vec = Vec::with_capacity(self.len() + other.len());
+        vec.extend_from_slice(self.as_slice());
+        vec.extend_from_slice(other.as_slice());
+        Bytes::from_vec(vec)
+    }
+
+    /// Encode the bytes as lowercase hexadecimal.
+    /// ### Returns
+    /// A native String of two hex digits per byte
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(&[0x00, 0xab, 0xff]);
+    /// assert_eq!(b.tohex(), "00abff");
+    /// ```
+
+    pub fn tohex(&self) -> std::string::String {
+        let mut s = std::string::String::with_capacity(self.len() * 2);
+        for &byte in self.as_slice() {
+            s.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+            s.push(HEX_DIGITS[(byte & 0xF) as usize] as char);
+        }
+        s
+    }
+
+    /// Encode the bytes as standard padded base64.
+    /// ### Returns
+    /// A native String of base64 text
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::Bytes::from(b"hello").tobase64(), "aGVsbG8=");
+    /// assert_eq!(shsc::Bytes::from(b"hi!").tobase64(), "aGkh");
+    /// ```
+
+    pub fn tobase64(&self) -> std::string::String {
+        let mut s = std::string::String::with_capacity(self.len().div_ceil(3) * 4);
+        for chunk in self.as_slice().chunks(3) {
+            let b0 = chunk[0] as u32;
+            let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+            let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+            let n = (b0 << 16) | (b1 << 8) | b2;
+            s.push(BASE64_DIGITS[(n >> 18) as usize & 63] as char);
+            s.push(BASE64_DIGITS[(n >> 12) as usize & 63] as char);
+            if chunk.len() > 1 {
+                s.push(BASE64_DIGITS[(n >> 6) as usize & 63] as char);
+            } else {
+                s.push('=');
+            }
+            if chunk.len() > 2 {
+                s.push(BASE64_DIGITS[n as usize & 63] as char);
+            } else {
+                s.push('=');
+            }
+        }
+        s
+    }
+}
+
+impl traits::Append<u8> for Bytes {
+    /// Implement the Append trait for the Bytes struct.
+    /// ### Arguments
+    /// * `value` - A native byte
+    /// ### Example
+    /// ```
+    /// use shsc::traits::Append;
+    /// let mut b = shsc::Bytes::new();
+    /// b.append(1u8);
+    /// assert_eq!(b.as_slice(), &[1]);
+    /// ```
+
+    fn append(&mut self, value: u8) {
+        unsafe { (*self.buf).push(value) };
+    }
+}
+
+impl traits::Append<&[u8]> for Bytes {
+    /// Implement the Append trait for the Bytes struct.
+    /// ### Arguments
+    /// * `value` - A native byte slice
+    /// ### Example
+    /// ```
+    /// use shsc::traits::Append;
+    /// let mut b = shsc::Bytes::from(b"ab");
+    /// b.append(&b"cd"[..]);
+    /// assert_eq!(b.as_slice(), b"abcd");
+    /// ```
+
+    fn append(&mut self, value: &[u8]) {
+        unsafe { (*self.buf).extend_from_slice(value) };
+    }
+}
+
+impl ops::Index<usize> for Bytes {
+    type Output = u8;
+
+    /// Get a reference to the byte at index.
+    /// ### Example
+    /// ```
+    /// let b = shsc::Bytes::from(&[1, 2, 3]);
+    /// assert_eq!(b[2], 3);
+    /// ```
+
+    fn index(&self, index: usize) -> &u8 {
+        self.as_slice()
+            .get(index)
+            .expect(&format!("shsc::Bytes: index: undefined data at {}", index))
+    }
+}
+
+impl ops::IndexMut<usize> for Bytes {
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        self.as_mut_slice().get_mut(index).expect(&format!(
+            "shsc::Bytes: index_mut: undefined data at {}",
+            index
+        ))
+    }
+}
+
+impl traits::ToStr for Bytes {
+    /// Implement the ToStr trait for the Bytes struct.
+    /// Bytes are written as lowercase hexadecimal, see Bytes::tohex.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::Bytes::from(b"AB").tostr(), "4142");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        self.tohex()
+    }
+}
+
+impl traits::RefCopy for Bytes {
+    /// Implement the RefCopy trait for the Bytes struct.
+    /// This allows us to create a reference copy sharing the same buffer.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::{Append, RefCopy};
+    /// let mut b = shsc::Bytes::new();
+    /// let mut b2 = b.refcopy();
+    /// b2.append(7u8);
+    /// assert_eq!(b.as_slice(), &[7]);
+    /// ```
+
+    fn refcopy(&mut self) -> Self {
+        self.incrc();
+        Bytes {
+            buf: self.buf,
+            refc: self.refc,
+        }
+    }
+
+    fn refdrop(self) {
+        // drop trait is called here
+    }
+}
+
+impl Clone for Bytes {
+    /// Implement the Clone trait for the Bytes struct.
+    /// Results in a new Bytes struct with its own buffer and a reference count of 1.
+    fn clone(&self) -> Self {
+        Bytes::from(self.as_slice())
+    }
+}
+
+impl PartialEq for Bytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for Bytes {}
+
+impl PartialOrd for Bytes {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bytes {
+    /// Implement the Ord trait for the Bytes struct.
+    /// Bytes compare lexicographically.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl Hash for Bytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl traits::RefC for Bytes {
+    fn incrc(&mut self) {
+        unsafe { *self.refc += 1 };
+    }
+
+    fn decrc(&mut self) {
+        unsafe {
+            *self.refc -= 1;
+            if (*self.refc) < 0 {
+                *self.refc = 0;
+            }
+        }
+    }
+
+    fn getrc(&self) -> i64 {
+        unsafe { *self.refc }
+    }
+}
+
+impl Drop for Bytes {
+    /// Implement the Drop trait for the Bytes struct.
+    /// The buffer is freed when the reference count reaches zero.
+    fn drop(&mut self) {
+        self.decrc();
+        if self.getrc() > 0 {
+            return;
+        }
+        unsafe {
+            drop(Box::from_raw(self.buf));
+            drop(Box::from_raw(self.refc));
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Compact 64-bit representation of shsc::Data.
+//!
+//! A [`Value`] packs a value into a single NaN-boxed word instead of the full
+//! `Data` enum. Floats are stored as their own bits, with every NaN folded
+//! into one canonical quiet NaN. The negative quiet NaN space is then free to
+//! carry a 3 bit tag and a 48 bit payload:
+//!
+//! | tag | payload |
+//! |-----|---------|
+//! | NULL | none |
+//! | SCALAR | BOOL, CHAR and the 8 to 32 bit integers, with their type |
+//! | INT | INT64 values that fit in 48 bits |
+//! | UINT | UINT64 values that fit in 48 bits |
+//! | HEAP | pointer to a boxed shsc::Data holding anything else |
+//!
+//! [`List`] stores values contiguously, eight bytes per item. Conversions to
+//! and from shsc::Data keep the exact type, so the `Data` API is unchanged.
+//! See `examples/compact.rs` for a comparison with shsc::List.
+//!
+//! This is a separate storage to opt into: shsc::Data and shsc::List keep
+//! their own representation, and values are converted as they go into and
+//! come out of a compact::List.
+//!
+//! HEAP pointers are stored without the low bits that the alignment of Data
+//! keeps zero, so addresses up to 51 bits wide fit. That covers the user
+//! address space of current 64-bit platforms unless a process asks for
+//! addresses above it; boxing a value at a wider address panics.
+//!
+//! ### Example
+//! ```
+//! let value = shsc::compact::Value::from(shsc::todata!(2.5));
+//! assert_eq!(std::mem::size_of_val(&value), 8);
+//! assert_eq!(value.as_f64(), Some(2.5));
+//! assert_eq!(value.to_data().typename(), "FLOAT64");
+//! ```
+
+use crate::traits;
+use crate::DataTypes;
+
+const QUIET_NAN: u64 = 0x7FF8_0000_0000_0000;
+const BOXED: u64 = 0xFFF8_0000_0000_0000;
+const TAG_SHIFT: u32 = 48;
+const PAYLOAD: u64 = (1 << TAG_SHIFT) - 1;
+// low bits of a heap address that are always zero
+const HEAP_SHIFT: u32 = std::mem::align_of::<crate::Data>().trailing_zeros();
+
+const TAG_NULL: u64 = 1;
+const TAG_SCALAR: u64 = 2;
+const TAG_INT: u64 = 3;
+const TAG_UINT: u64 = 4;
+const TAG_HEAP: u64 = 5;
+
+// scalar kinds, stored above the 32 bit scalar value
+const KIND_SHIFT: u32 = 32;
+const KIND_BOOL: u64 = 0;
+const KIND_UINT8: u64 = 1;
+const KIND_UINT16: u64 = 2;
+const KIND_UINT32: u64 = 3;
+const KIND_INT8: u64 = 4;
+const KIND_INT16: u64 = 5;
+const KIND_INT32: u64 = 6;
+const KIND_CHAR: u64 = 7;
+
+const INT_MIN: i64 = -(1 << (TAG_SHIFT - 1));
+const INT_MAX: i64 = (1 << (TAG_SHIFT - 1)) - 1;
+
+pub struct Value {
+    bits: u64,
+}
+
+impl Value {
+    /// The NULL value.
+    /// ### Example
+    /// ```
+    /// assert!(shsc::compact::Value::null().is_null());
+    /// ```
+
+    pub fn null() -> Self {
+        Value::boxed(TAG_NULL, 0)
+    }
+
+    fn boxed(tag: u64, payload: u64) -> Self {
+        Value {
+            bits: BOXED | (tag << TAG_SHIFT) | (payload & PAYLOAD),
+        }
+    }
+
+    fn scalar(kind: u64, value: u32) -> Self {
+        Value::boxed(TAG_SCALAR, (kind << KIND_SHIFT) | value as u64)
+    }
+
+    fn heap(data: crate::Data) -> Self {
+        let ptr = Box::into_raw(Box::new(data)) as u64 >> HEAP_SHIFT;
+        assert!(
+            ptr & !PAYLOAD == 0,
+            "shsc::compact::Value: heap address does not fit in {} bits",
+            TAG_SHIFT + HEAP_SHIFT
+        );
+        Value::boxed(TAG_HEAP, ptr)
+    }
+
+    fn tag(&self) -> Option<u64> {
+        if self.bits & BOXED == BOXED {
+            Some((self.bits >> TAG_SHIFT) & 0x7)
+        } else {
+            None
+        }
+    }
+
+    fn payload(&self) -> u64 {
+        self.bits & PAYLOAD
+    }
+
+    fn heap_ptr(&self) -> Option<*mut crate::Data> {
+        match self.tag() {
+            Some(TAG_HEAP) => Some(self.payload_ptr()),
+            _ => None,
+        }
+    }
+
+    /// Check whether the value is NULL.
+    pub fn is_null(&self) -> bool {
+        self.tag() == Some(TAG_NULL)
+    }
+
+    /// Get the value as an f64 if it is a FLOAT64, without decoding to shsc::Data.
+    /// ### Example
+    /// ```
+    /// use shsc::compact::Value;
+    /// assert!(Value::from(shsc::todata!(f64::NAN)).as_f64().unwrap().is_nan());
+    /// assert_eq!(Value::from(shsc::todata!(1)).as_f64(), None);
+    /// ```
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.tag() {
+            None => Some(f64::from_bits(self.bits)),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an i64 if it is an INT64, without decoding to shsc::Data.
+    /// ### Example
+    /// ```
+    /// use shsc::compact::Value;
+    /// assert_eq!(Value::from(shsc::todata!(-5i64)).as_i64(), Some(-5));
+    /// assert_eq!(Value::from(shsc::todata!(i64::MIN)).as_i64(), Some(i64::MIN));
+    /// ```
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.tag() {
+            // sign extend the 48 bit payload
+            Some(TAG_INT) => Some(((self.payload() << 16) as i64) >> 16),
+            Some(TAG_HEAP) => match unsafe { &(*self.payload_ptr()).data } {
+                DataTypes::INT64(value) => Some(*value),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Get the value as a bool if it is a BOOL.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::compact::Value::from(shsc::todata!(true)).as_bool(), Some(true));
+    /// ```
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.tag() {
+            Some(TAG_SCALAR) if self.payload() >> KIND_SHIFT == KIND_BOOL => {
+                Some(self.payload() & 1 == 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get a reference to the boxed shsc::Data for values that do not fit in a word.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let value = shsc::compact::Value::from(shsc::todata!("hello"));
+    /// assert_eq!(value.as_data().unwrap().tostr(), "hello");
+    /// assert!(shsc::compact::Value::from(shsc::todata!(1)).as_data().is_none());
+    /// ```
+
+    pub fn as_data(&self) -> Option<&crate::Data> {
+        self.heap_ptr().map(|ptr| unsafe { &*ptr })
+    }
+
+    /// Get the type name of the value, as shsc::Data::typename would.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::compact::Value::from(shsc::todata!(1u8)).typename(), "UINT8");
+    /// ```
+
+    pub fn typename(&self) -> std::string::String {
+        match self.as_data() {
+            Some(data) => data.typename(),
+            None => self.to_data().typename(),
+        }
+    }
+
+    /// Decode into a shsc::Data.
+    /// Values held on the heap are deep copied, as shsc::Data::clone does.
+    /// ### Example
+    /// ```
+    /// let value = shsc::compact::Value::from(shsc::todata!('x'));
+    /// assert_eq!(value.to_data().as_char(), Some('x'));
+    /// ```
+
+    pub fn to_data(&self) -> crate::Data {
+        if let Some(data) = self.as_data() {
+            return data.clone();
+        }
+        self.decode()
+    }
+
+    /// Decode into a shsc::Data, moving values held on the heap out.
+    /// ### Example
+    /// ```
+    /// let value = shsc::compact::Value::from(shsc::todata!(u64::MAX));
+    /// assert_eq!(value.into_data().as_u64(), Some(u64::MAX));
+    /// ```
+
+    pub fn into_data(self) -> crate::Data {
+        let value = std::mem::ManuallyDrop::new(self);
+        match value.heap_ptr() {
+            Some(ptr) => *unsafe { Box::from_raw(ptr) },
+            None => value.decode(),
+        }
+    }
+
+    fn payload_ptr(&self) -> *mut crate::Data {
+        (self.payload() << HEAP_SHIFT) as *mut crate::Data
+    }
+
+    /// Decode a value that is not held on the heap.
+    fn decode(&self) -> crate::Data {
+        let payload = self.payload();
+        let data = match self.tag() {
+            None => DataTypes::FLOAT64(f64::from_bits(self.bits)),
+            Some(TAG_NULL) => DataTypes::NULL,
+            Some(TAG_INT) => DataTypes::INT64(((payload << 16) as i64) >> 16),
+            Some(TAG_UINT) => DataTypes::UINT64(payload),
+            Some(TAG_SCALAR) => {
+                let value = payload as u32;
+                match payload >> KIND_SHIFT {
+                    KIND_BOOL => DataTypes::BOOL(value == 1),
+                    KIND_UINT8 => DataTypes::UINT8(value as u8),
+                    KIND_UINT16 => DataTypes::UINT16(value as u16),
+                    KIND_UINT32 => DataTypes::UINT32(value),
+                    KIND_INT8 => DataTypes::INT8(value as i8),
+                    KIND_INT16 => DataTypes::INT16(value as i16),
+                    KIND_INT32 => DataTypes::INT32(value as i32),
+                    KIND_CHAR => DataTypes::CHAR(
+                        char::from_u32(value).expect("shsc::compact::Value: invalid char"),
+                    ),
+                    kind => panic!("shsc::compact::Value: invalid scalar kind {}", kind),
+                }
+            }
+            Some(tag) => panic!("shsc::compact::Value: invalid tag {}", tag),
+        };
+        crate::Data { data }
+    }
+}
+
+impl From<crate::Data> for Value {
+    /// Encode a shsc::Data, boxing it if it does not fit in a word.
+    /// ### Example
+    /// ```
+    /// use shsc::compact::Value;
+    /// for data in [shsc::todata!(-3i8), shsc::todata!(1u64 << 50), shsc::todata!("s")] {
+    ///     let name = data.typename();
+    ///     assert_eq!(Value::from(data).typename(), name);
+    /// }
+    /// ```
+
+    fn from(data: crate::Data) -> Self {
+        match data.data {
+            DataTypes::FLOAT64(value) if value.is_nan() => Value { bits: QUIET_NAN },
+            DataTypes::FLOAT64(value) => Value {
+                bits: value.to_bits(),
+            },
+            DataTypes::NULL => Value::null(),
+            DataTypes::BOOL(value) => Value::scalar(KIND_BOOL, value as u32),
+            DataTypes::UINT8(value) => Value::scalar(KIND_UINT8, value as u32),
+            DataTypes::UINT16(value) => Value::scalar(KIND_UINT16, value as u32),
+            DataTypes::UINT32(value) => Value::scalar(KIND_UINT32, value),
+            DataTypes::INT8(value) => Value::scalar(KIND_INT8, value as u8 as u32),
+            DataTypes::INT16(value) => Value::scalar(KIND_INT16, value as u16 as u32),
+            DataTypes::INT32(value) => Value::scalar(KIND_INT32, value as u32),
+            DataTypes::CHAR(value) => Value::scalar(KIND_CHAR, value as u32),
+            DataTypes::INT64(value) if (INT_MIN..=INT_MAX).contains(&value) => {
+                Value::boxed(TAG_INT, value as u64)
+            }
+            DataTypes::UINT64(value) if value <= PAYLOAD => Value::boxed(TAG_UINT, value),
+            data => Value::heap(crate::Data { data }),
+        }
+    }
+}
+
+impl Clone for Value {
+    /// Implement the Clone trait for the Value struct.
+    /// Values held on the heap are deep copied, as shsc::Data::clone does.
+    fn clone(&self) -> Self {
+        match self.as_data() {
+            Some(data) => Value::heap(data.clone()),
+            None => Value { bits: self.bits },
+        }
+    }
+}
+
+impl traits::ToStr for Value {
+    /// Implement the ToStr trait for the Value struct.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::compact::Value::from(shsc::todata!(7)).tostr(), "7");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        match self.as_data() {
+            Some(data) => data.tostr(),
+            None => self.decode().tostr(),
+        }
+    }
+}
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        if let Some(ptr) = self.heap_ptr() {
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+}
+
+/// A list of compact values, stored contiguously.
+pub struct List {
+    items: Vec<Value>,
+}
+
+impl List {
+    /// Create a new empty list.
+    /// ### Example
+    /// ```
+    /// let list = shsc::compact::List::new();
+    /// assert_eq!(list.len(), 0);
+    /// ```
+
+    pub fn new() -> Self {
+        List { items: Vec::new() }
+    }
+
+    /// Create a compact list holding a copy of a shsc::List.
+    /// ### Arguments
+    /// * `list` - The list to copy
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2.5)]);
+    /// let compact = shsc::compact::List::from(&list);
+    /// assert_eq!(compact.len(), 2);
+    /// ```
+
+    pub fn from(list: &crate::List) -> Self {
+        List {
+            items: list.iter().map(|data| Value::from(data.clone())).collect(),
+        }
+    }
+
+    /// Append data to the list.
+    /// ### Arguments
+    /// * `data` - Data to append
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::compact::List::new();
+    /// list.append(shsc::todata!(1.5));
+    /// assert_eq!(list.value(0).unwrap().as_f64(), Some(1.5));
+    /// ```
+
+    pub fn append(&mut self, data: crate::Data) {
+        self.items.push(Value::from(data));
+    }
+
+    /// Get a copy of the data at the specified index.
+    /// ### Arguments
+    /// * `index` - Index of data
+    /// ### Returns
+    /// The decoded data, or None if the index is out of bounds
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::compact::List::new();
+    /// list.append(shsc::todata!(true));
+    /// assert_eq!(list.get(0).unwrap().as_bool(), Some(true));
+    /// assert!(list.get(1).is_none());
+    /// ```
+
+    pub fn get(&self, index: usize) -> Option<crate::Data> {
+        self.items.get(index).map(Value::to_data)
+    }
+
+    /// Get the compact value at the specified index.
+    pub fn value(&self, index: usize) -> Option<&Value> {
+        self.items.get(index)
+    }
+
+    /// Get the compact values as a slice.
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1.0), shsc::todata!(2.0)]);
+    /// let compact = shsc::compact::List::from(&list);
+    /// let sum: f64 = compact.values().iter().filter_map(|v| v.as_f64()).sum();
+    /// assert_eq!(sum, 3.0);
+    /// ```
+
+    pub fn values(&self) -> &[Value] {
+        &self.items
+    }
+
+    /// Get the length of the list.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Decode into a shsc::List.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!("a")]);
+    /// assert_eq!(shsc::compact::List::from(&list).to_list().tostr(), list.tostr());
+    /// ```
+
+    pub fn to_list(&self) -> crate::List {
+        let mut list = crate::List::new();
+        for value in self.items.iter() {
+            list.append(value.to_data());
+        }
+        list
+    }
+}
+
+impl Clone for List {
+    fn clone(&self) -> Self {
+        List {
+            items: self.items.clone(),
+        }
+    }
+}
+
+impl traits::ToStr for List {
+    /// Implement the ToStr trait for the compact List struct.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list = shsc::compact::List::new();
+    /// list.append(shsc::todata!(1));
+    /// list.append(shsc::todata!(2));
+    /// assert_eq!(list.tostr(), "[1,2]");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        self.to_list().tostr()
+    }
+}
@@ -1,8 +1,13 @@
-use crate::traits::{self, RefC};
+use crate::traits;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
 /// Converts the given data into a `Data` instance using `shsc::Data::from`.
 /// ### Arguments
 /// * `data` - The input data to be converted.
+///
 /// This macro is a convenient wrapper around `shsc::Data::from`.
 /// ### Examples
 /// ```
@@ -29,8 +34,12 @@ pub enum DataTypes {
     FLOAT64(f64),
     CHAR(char),
     STRING(crate::String),
+    BYTES(crate::Bytes),
     LIST(crate::List),
     MAP(crate::Map),
+    FUNCTION(crate::Function),
+    USERDATA(crate::UserData),
+    WEAK(crate::Weak),
     NULL,
 }
 
@@ -49,8 +58,12 @@ impl DataTypes {
             DataTypes::FLOAT64(_) => std::string::String::from("FLOAT64"),
             DataTypes::CHAR(_) => std::string::String::from("CHAR"),
             DataTypes::STRING(_) => std::string::String::from("STRING"),
+            DataTypes::BYTES(_) => std::string::String::from("BYTES"),
             DataTypes::LIST(_) => std::string::String::from("LIST"),
             DataTypes::MAP(_) => std::string::String::from("MAP"),
+            DataTypes::FUNCTION(_) => std::string::String::from("FUNCTION"),
+            DataTypes::USERDATA(_) => std::string::String::from("USERDATA"),
+            DataTypes::WEAK(_) => std::string::String::from("WEAK"),
             DataTypes::NULL => std::string::String::from("NULL"),
         }
     }
@@ -60,6 +73,30 @@ pub struct Data {
     pub data: DataTypes,
 }
 
+/// Error returned when converting shsc::Data back to a native Rust type fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The data has the wrong type; holds the target type and the typename found.
+    TypeMismatch(&'static str, std::string::String),
+    /// The numeric value does not fit the target type; holds the target type and the typename found.
+    OutOfRange(&'static str, std::string::String),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::TypeMismatch(target, found) => {
+                write!(f, "shsc::Data: cannot convert {} to {}", found, target)
+            }
+            ConvertError::OutOfRange(target, found) => {
+                write!(f, "shsc::Data: {} value out of range for {}", found, target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
 impl Data {
     pub const NULL: crate::Data = crate::Data {
         data: crate::DataTypes::NULL,
@@ -83,11 +120,324 @@ impl Data {
     }
 
     pub fn is_null(&self) -> bool {
+        matches!(&self.data, DataTypes::NULL)
+    }
+
+    /// Get the value of any integer type, widened to i128.
+    fn integer(&self) -> Option<i128> {
+        match self.data {
+            DataTypes::UINT8(value) => Some(value as i128),
+            DataTypes::UINT16(value) => Some(value as i128),
+            DataTypes::UINT32(value) => Some(value as i128),
+            DataTypes::UINT64(value) => Some(value as i128),
+            DataTypes::INT8(value) => Some(value as i128),
+            DataTypes::INT16(value) => Some(value as i128),
+            DataTypes::INT32(value) => Some(value as i128),
+            DataTypes::INT64(value) => Some(value as i128),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a bool if the data is a BOOL.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!(true).as_bool(), Some(true));
+    /// assert_eq!(shsc::todata!(1).as_bool(), None);
+    /// ```
+
+    pub fn as_bool(&self) -> Option<bool> {
+        bool::try_from(self).ok()
+    }
+
+    /// Get the value as an i64 if the data is an integer that fits.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!(7u8).as_i64(), Some(7));
+    /// assert_eq!(shsc::todata!(u64::MAX).as_i64(), None);
+    /// assert_eq!(shsc::todata!(7.0).as_i64(), None);
+    /// ```
+
+    pub fn as_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    /// Get the value as a u64 if the data is a non-negative integer.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!(7i8).as_u64(), Some(7));
+    /// assert_eq!(shsc::todata!(-7i8).as_u64(), None);
+    /// ```
+
+    pub fn as_u64(&self) -> Option<u64> {
+        u64::try_from(self).ok()
+    }
+
+    /// Get the value as an f64 if the data is a FLOAT64, or an integer that f64 represents exactly.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!(2.5).as_f64(), Some(2.5));
+    /// assert_eq!(shsc::todata!(2).as_f64(), Some(2.0));
+    /// assert_eq!(shsc::todata!(u64::MAX).as_f64(), None);
+    /// ```
+
+    pub fn as_f64(&self) -> Option<f64> {
+        f64::try_from(self).ok()
+    }
+
+    /// Get the value as a char if the data is a CHAR.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::todata!('x').as_char(), Some('x'));
+    /// ```
+
+    pub fn as_char(&self) -> Option<char> {
+        char::try_from(self).ok()
+    }
+
+    /// Get a reference to the shsc::String if the data is a STRING.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let data = shsc::todata!("hello");
+    /// assert_eq!(data.as_string().unwrap().tostr(), "hello");
+    /// ```
+
+    pub fn as_string(&self) -> Option<&crate::String> {
         match &self.data {
-            DataTypes::NULL => true,
-            _ => false,
+            DataTypes::STRING(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the shsc::String if the data is a STRING.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::{Append, ToStr};
+    /// let mut data = shsc::todata!("hello");
+    /// data.as_string_mut().unwrap().append('!');
+    /// assert_eq!(data.tostr(), "hello!");
+    /// ```
+
+    pub fn as_string_mut(&mut self) -> Option<&mut crate::String> {
+        match &mut self.data {
+            DataTypes::STRING(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the string as a native str if the data is a STRING.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!("hello");
+    /// assert_eq!(data.as_str(), Some("hello"));
+    /// assert_eq!(shsc::todata!(1).as_str(), None);
+    /// ```
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.data {
+            DataTypes::STRING(value) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Get the bytes as a native slice if the data is BYTES.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!(vec![1u8, 2, 3]);
+    /// assert_eq!(data.as_bytes(), Some(&[1u8, 2, 3][..]));
+    /// ```
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.data {
+            DataTypes::BYTES(value) => Some(value.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the shsc::Bytes if the data is BYTES.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::Append;
+    /// let mut data = shsc::todata!(&b"ab"[..]);
+    /// data.as_bytes_mut().unwrap().append(b'c');
+    /// assert_eq!(data.as_bytes(), Some(&b"abc"[..]));
+    /// ```
+
+    pub fn as_bytes_mut(&mut self) -> Option<&mut crate::Bytes> {
+        match &mut self.data {
+            DataTypes::BYTES(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a reference to the shsc::UserData if the data is USERDATA.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!(shsc::UserData::new(5u32, "HANDLE"));
+    /// assert_eq!(data.as_userdata().unwrap().downcast_ref::<u32>(), Some(&5));
+    /// ```
+
+    pub fn as_userdata(&self) -> Option<&crate::UserData> {
+        match &self.data {
+            DataTypes::USERDATA(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the shsc::UserData if the data is USERDATA.
+    /// ### Example
+    /// ```
+    /// let mut data = shsc::todata!(shsc::UserData::new(5u32, "HANDLE"));
+    /// *data.as_userdata_mut().unwrap().downcast_mut::<u32>().unwrap() += 1;
+    /// assert_eq!(data.as_userdata().unwrap().downcast_ref::<u32>(), Some(&6));
+    /// ```
+
+    pub fn as_userdata_mut(&mut self) -> Option<&mut crate::UserData> {
+        match &mut self.data {
+            DataTypes::USERDATA(value) => Some(value),
+            _ => None,
         }
     }
+
+    /// Get a reference to the shsc::Weak if the data is WEAK.
+    /// ### Example
+    /// ```
+    /// let m = shsc::Map::new();
+    /// let data = shsc::todata!(m.downgrade());
+    /// assert!(data.as_weak().unwrap().upgrade().is_some());
+    /// ```
+
+    pub fn as_weak(&self) -> Option<&crate::Weak> {
+        match &self.data {
+            DataTypes::WEAK(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a reference to the shsc::List if the data is a LIST.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!(vec![shsc::todata!(1)]);
+    /// assert_eq!(data.as_list().unwrap().len(), 1);
+    /// assert!(shsc::todata!(1).as_list().is_none());
+    /// ```
+
+    pub fn as_list(&self) -> Option<&crate::List> {
+        match &self.data {
+            DataTypes::LIST(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the shsc::List if the data is a LIST.
+    /// ### Example
+    /// ```
+    /// let mut data = shsc::todata!(vec![shsc::todata!(1)]);
+    /// data.as_list_mut().unwrap().append(shsc::todata!(2));
+    /// assert_eq!(data.as_list().unwrap().len(), 2);
+    /// ```
+
+    pub fn as_list_mut(&mut self) -> Option<&mut crate::List> {
+        match &mut self.data {
+            DataTypes::LIST(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a reference to the shsc::Map if the data is a MAP.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!(shsc::Map::new());
+    /// assert!(data.as_map().unwrap().get("key").is_none());
+    /// ```
+
+    pub fn as_map(&self) -> Option<&crate::Map> {
+        match &self.data {
+            DataTypes::MAP(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the shsc::Map if the data is a MAP.
+    /// ### Example
+    /// ```
+    /// let mut data = shsc::todata!(shsc::Map::new());
+    /// data.as_map_mut().unwrap().insert("key", shsc::todata!(1));
+    /// assert_eq!(data.as_map().unwrap()["key"].as_i64(), Some(1));
+    /// ```
+
+    pub fn as_map_mut(&mut self) -> Option<&mut crate::Map> {
+        match &mut self.data {
+            DataTypes::MAP(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Call the data if it is a FUNCTION.
+    /// ### Arguments
+    /// * `args` - The call arguments
+    /// ### Returns
+    /// The return value, or a CallError if the data is not callable or the call fails
+    /// ### Example
+    /// ```
+    /// let f = shsc::todata!(shsc::Function::native(|args| Ok(-&args[0])));
+    /// assert!(f.call(&mut [shsc::todata!(1)]).unwrap() == shsc::todata!(-1));
+    /// assert!(shsc::todata!(1).call(&mut []).is_err());
+    /// ```
+
+    pub fn call(&self, args: &mut [Data]) -> Result<Data, crate::function::CallError> {
+        match &self.data {
+            DataTypes::FUNCTION(func) => func.call(args),
+            _ => Err(crate::function::CallError::NotCallable(self.typename())),
+        }
+    }
+
+    /// Position of the type in the cross-type ordering used by Ord.
+    fn rank(&self) -> u8 {
+        match &self.data {
+            DataTypes::NULL => 0,
+            DataTypes::BOOL(_) => 1,
+            DataTypes::UINT8(_) => 2,
+            DataTypes::UINT16(_) => 3,
+            DataTypes::UINT32(_) => 4,
+            DataTypes::UINT64(_) => 5,
+            DataTypes::INT8(_) => 6,
+            DataTypes::INT16(_) => 7,
+            DataTypes::INT32(_) => 8,
+            DataTypes::INT64(_) => 9,
+            DataTypes::FLOAT64(_) => 10,
+            DataTypes::CHAR(_) => 11,
+            DataTypes::STRING(_) => 12,
+            DataTypes::BYTES(_) => 13,
+            DataTypes::LIST(_) => 14,
+            DataTypes::MAP(_) => 15,
+            DataTypes::FUNCTION(_) => 16,
+            DataTypes::USERDATA(_) => 17,
+            DataTypes::WEAK(_) => 18,
+        }
+    }
+}
+
+/// Total order on f64: all NaNs are equal and greater than every other value,
+/// and -0.0 equals 0.0.
+fn cmp_f64(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap(),
+    }
+}
+
+/// Bits of an f64 that agree with cmp_f64: one NaN and one zero.
+fn f64_hash_bits(value: f64) -> u64 {
+    if value.is_nan() {
+        f64::NAN.to_bits()
+    } else if value == 0.0 {
+        0
+    } else {
+        value.to_bits()
+    }
 }
 
 impl From<bool> for Data {
@@ -194,6 +544,30 @@ impl From<crate::String> for Data {
     }
 }
 
+impl From<Vec<u8>> for Data {
+    fn from(value: Vec<u8>) -> Self {
+        crate::Data {
+            data: crate::DataTypes::BYTES(crate::Bytes::from_vec(value)),
+        }
+    }
+}
+
+impl From<&[u8]> for Data {
+    fn from(value: &[u8]) -> Self {
+        crate::Data {
+            data: crate::DataTypes::BYTES(crate::Bytes::from(value)),
+        }
+    }
+}
+
+impl From<crate::Bytes> for Data {
+    fn from(value: crate::Bytes) -> Self {
+        crate::Data {
+            data: crate::DataTypes::BYTES(value),
+        }
+    }
+}
+
 impl From<Vec<Data>> for Data {
     fn from(value: Vec<Data>) -> Self {
         crate::Data {
@@ -218,6 +592,287 @@ impl From<crate::Map> for Data {
     }
 }
 
+impl TryFrom<&Data> for u8 {
+    type Error = ConvertError;
+
+    /// Convert any integer type to a native integer, failing if the value does not fit.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!(300i64);
+    /// assert_eq!(u16::try_from(&data), Ok(300));
+    /// assert!(u8::try_from(&data).is_err());
+    /// assert!(u8::try_from(&shsc::todata!("300")).is_err());
+    /// ```
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("u8", value.typename()))?;
+        u8::try_from(integer).map_err(|_| ConvertError::OutOfRange("u8", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for u16 {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("u16", value.typename()))?;
+        u16::try_from(integer).map_err(|_| ConvertError::OutOfRange("u16", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for u32 {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("u32", value.typename()))?;
+        u32::try_from(integer).map_err(|_| ConvertError::OutOfRange("u32", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for u64 {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("u64", value.typename()))?;
+        u64::try_from(integer).map_err(|_| ConvertError::OutOfRange("u64", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for i8 {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("i8", value.typename()))?;
+        i8::try_from(integer).map_err(|_| ConvertError::OutOfRange("i8", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for i16 {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("i16", value.typename()))?;
+        i16::try_from(integer).map_err(|_| ConvertError::OutOfRange("i16", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for i32 {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("i32", value.typename()))?;
+        i32::try_from(integer).map_err(|_| ConvertError::OutOfRange("i32", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for i64 {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("i64", value.typename()))?;
+        i64::try_from(integer).map_err(|_| ConvertError::OutOfRange("i64", value.typename()))
+    }
+}
+
+impl TryFrom<&Data> for f64 {
+    type Error = ConvertError;
+
+    /// Convert FLOAT64 to f64, or any integer type whose value f64 represents exactly.
+    /// ### Example
+    /// ```
+    /// assert_eq!(f64::try_from(&shsc::todata!(1.5)), Ok(1.5));
+    /// assert_eq!(f64::try_from(&shsc::todata!(-3i8)), Ok(-3.0));
+    /// assert!(f64::try_from(&shsc::todata!(i64::MAX)).is_err());
+    /// ```
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        if let DataTypes::FLOAT64(float) = value.data {
+            return Ok(float);
+        }
+        let integer = value
+            .integer()
+            .ok_or_else(|| ConvertError::TypeMismatch("f64", value.typename()))?;
+        let float = integer as f64;
+        if float as i128 != integer {
+            return Err(ConvertError::OutOfRange("f64", value.typename()));
+        }
+        Ok(float)
+    }
+}
+
+impl TryFrom<&Data> for bool {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        match value.data {
+            DataTypes::BOOL(value) => Ok(value),
+            _ => Err(ConvertError::TypeMismatch("bool", value.typename())),
+        }
+    }
+}
+
+impl TryFrom<&Data> for char {
+    type Error = ConvertError;
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        match value.data {
+            DataTypes::CHAR(value) => Ok(value),
+            _ => Err(ConvertError::TypeMismatch("char", value.typename())),
+        }
+    }
+}
+
+impl TryFrom<&Data> for std::string::String {
+    type Error = ConvertError;
+
+    /// Convert a STRING to a native String.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!("hello");
+    /// assert_eq!(String::try_from(&data).unwrap(), "hello");
+    /// let err = String::try_from(&shsc::todata!(1)).unwrap_err();
+    /// assert_eq!(err.to_string(), "shsc::Data: cannot convert INT32 to String");
+    /// ```
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        match &value.data {
+            DataTypes::STRING(value) => Ok(value.as_str().to_owned()),
+            _ => Err(ConvertError::TypeMismatch("String", value.typename())),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Data> for &'a str {
+    type Error = ConvertError;
+
+    /// Borrow a STRING as a native str.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!("hello");
+    /// assert_eq!(<&str>::try_from(&data), Ok("hello"));
+    /// assert!(<&str>::try_from(&shsc::todata!(1)).is_err());
+    /// ```
+
+    fn try_from(value: &'a Data) -> Result<Self, Self::Error> {
+        match &value.data {
+            DataTypes::STRING(value) => Ok(value.as_str()),
+            _ => Err(ConvertError::TypeMismatch("str", value.typename())),
+        }
+    }
+}
+
+impl<T> TryFrom<&Data> for Vec<T>
+where
+    T: for<'a> TryFrom<&'a Data, Error = ConvertError>,
+{
+    type Error = ConvertError;
+
+    /// Convert a LIST to a native Vec, converting every element.
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!(vec![shsc::todata!(1), shsc::todata!(2u8)]);
+    /// assert_eq!(Vec::<i64>::try_from(&data), Ok(vec![1, 2]));
+    /// assert!(Vec::<bool>::try_from(&data).is_err());
+    /// ```
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        match &value.data {
+            DataTypes::LIST(list) => list.iter().map(T::try_from).collect(),
+            _ => Err(ConvertError::TypeMismatch("Vec", value.typename())),
+        }
+    }
+}
+
+impl<T> TryFrom<&Data> for HashMap<std::string::String, T>
+where
+    T: for<'a> TryFrom<&'a Data, Error = ConvertError>,
+{
+    type Error = ConvertError;
+
+    /// Convert a MAP to a native HashMap, converting every value.
+    /// ### Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// let mut m = shsc::Map::new();
+    /// m.insert("key", shsc::todata!(1.5));
+    /// let map = HashMap::<String, f64>::try_from(&shsc::todata!(m)).unwrap();
+    /// assert_eq!(map["key"], 1.5);
+    /// ```
+
+    fn try_from(value: &Data) -> Result<Self, Self::Error> {
+        match &value.data {
+            DataTypes::MAP(map) => map
+                .iter()
+                .map(|(key, value)| Ok((key.to_owned(), T::try_from(value)?)))
+                .collect(),
+            _ => Err(ConvertError::TypeMismatch("HashMap", value.typename())),
+        }
+    }
+}
+
+impl From<crate::Function> for Data {
+    fn from(value: crate::Function) -> Self {
+        crate::Data {
+            data: crate::DataTypes::FUNCTION(value),
+        }
+    }
+}
+
+impl From<crate::function::NativeFn> for Data {
+    fn from(value: crate::function::NativeFn) -> Self {
+        crate::Data {
+            data: crate::DataTypes::FUNCTION(crate::Function::native(value)),
+        }
+    }
+}
+
+impl From<crate::UserData> for Data {
+    fn from(value: crate::UserData) -> Self {
+        crate::Data {
+            data: crate::DataTypes::USERDATA(value),
+        }
+    }
+}
+
+impl From<crate::Weak> for Data {
+    fn from(value: crate::Weak) -> Self {
+        crate::Data {
+            data: crate::DataTypes::WEAK(value),
+        }
+    }
+}
+
+impl From<crate::list::Weak> for Data {
+    fn from(value: crate::list::Weak) -> Self {
+        crate::Data {
+            data: crate::DataTypes::WEAK(crate::Weak::LIST(value)),
+        }
+    }
+}
+
+impl From<crate::map::Weak> for Data {
+    fn from(value: crate::map::Weak) -> Self {
+        crate::Data {
+            data: crate::DataTypes::WEAK(crate::Weak::MAP(value)),
+        }
+    }
+}
+
 impl Clone for Data {
     fn clone(&self) -> Self {
         match &self.data {
@@ -233,8 +888,13 @@ impl Clone for Data {
             crate::DataTypes::FLOAT64(value) => todata!(*value),
             crate::DataTypes::CHAR(value) => todata!(*value),
             crate::DataTypes::STRING(value) => todata!(value.clone()),
+            crate::DataTypes::BYTES(value) => todata!(value.clone()),
             crate::DataTypes::LIST(value) => todata!(value.clone()),
             crate::DataTypes::MAP(value) => todata!(value.clone()),
+            crate::DataTypes::FUNCTION(value) => todata!(value.clone()),
+            // host objects cannot be copied, the copy refers to the same one
+            crate::DataTypes::USERDATA(value) => todata!(value.share()),
+            crate::DataTypes::WEAK(value) => todata!(value.clone()),
             crate::DataTypes::NULL => Data::NULL,
         }
     }
@@ -255,8 +915,12 @@ impl traits::ToStr for Data {
             crate::DataTypes::FLOAT64(value) => value.to_string(),
             crate::DataTypes::CHAR(value) => value.to_string(),
             crate::DataTypes::STRING(value) => value.tostr(),
+            crate::DataTypes::BYTES(value) => value.tostr(),
             crate::DataTypes::LIST(value) => value.tostr(),
             crate::DataTypes::MAP(value) => value.tostr(),
+            crate::DataTypes::FUNCTION(value) => value.tostr(),
+            crate::DataTypes::USERDATA(value) => value.tostr(),
+            crate::DataTypes::WEAK(value) => value.tostr(),
             crate::DataTypes::NULL => std::string::String::from("NULL"),
         }
     }
@@ -266,8 +930,11 @@ impl traits::RefC for Data {
     fn incrc(&mut self) {
         match &mut self.data {
             crate::DataTypes::STRING(value) => value.incrc(),
+            crate::DataTypes::BYTES(value) => value.incrc(),
             crate::DataTypes::LIST(value) => value.incrc(),
             crate::DataTypes::MAP(value) => value.incrc(),
+            crate::DataTypes::FUNCTION(value) => value.incrc(),
+            crate::DataTypes::USERDATA(value) => value.incrc(),
             _ => (),
         }
     }
@@ -275,8 +942,11 @@ impl traits::RefC for Data {
     fn decrc(&mut self) {
         match &mut self.data {
             crate::DataTypes::STRING(value) => value.decrc(),
+            crate::DataTypes::BYTES(value) => value.decrc(),
             crate::DataTypes::LIST(value) => value.decrc(),
             crate::DataTypes::MAP(value) => value.decrc(),
+            crate::DataTypes::FUNCTION(value) => value.decrc(),
+            crate::DataTypes::USERDATA(value) => value.decrc(),
             _ => (),
         }
     }
@@ -284,8 +954,11 @@ impl traits::RefC for Data {
     fn getrc(&self) -> i64 {
         match &self.data {
             crate::DataTypes::STRING(value) => value.getrc(),
+            crate::DataTypes::BYTES(value) => value.getrc(),
             crate::DataTypes::LIST(value) => value.getrc(),
             crate::DataTypes::MAP(value) => value.getrc(),
+            crate::DataTypes::FUNCTION(value) => value.getrc(),
+            crate::DataTypes::USERDATA(value) => value.getrc(),
             _ => 0,
         }
     }
@@ -295,8 +968,11 @@ impl traits::RefCopy for Data {
     fn refcopy(&mut self) -> Self {
         match &mut self.data {
             crate::DataTypes::STRING(value) => todata!(value.refcopy()),
+            crate::DataTypes::BYTES(value) => todata!(value.refcopy()),
             crate::DataTypes::LIST(value) => todata!(value.refcopy()),
             crate::DataTypes::MAP(value) => todata!(value.refcopy()),
+            crate::DataTypes::FUNCTION(value) => todata!(value.refcopy()),
+            crate::DataTypes::USERDATA(value) => todata!(value.refcopy()),
             _ => self.clone(),
         }
     }
@@ -306,11 +982,114 @@ impl traits::RefCopy for Data {
     }
 }
 
-impl Drop for Data {
-    fn drop(&mut self) {
-        self.decrc();
-        if self.getrc() > 0 {
-            return;
+impl PartialEq for Data {
+    /// Deep structural equality, see the Ord implementation.
+    /// Values of different types are never equal, so INT8(1) != INT64(1).
+    /// ### Example
+    /// ```
+    /// assert!(shsc::todata!(1) == shsc::todata!(1));
+    /// assert!(shsc::todata!(1) != shsc::todata!(1.0));
+    /// assert!(shsc::todata!(f64::NAN) == shsc::todata!(f64::NAN));
+    /// assert!(shsc::todata!(vec![shsc::todata!("a")]) == shsc::todata!(vec![shsc::todata!("a")]));
+    /// ```
+
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Data {}
+
+impl PartialOrd for Data {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Data {
+    /// Total order over all data.
+    /// Values are ordered first by type rank, then by value within a type.
+    /// The rank order is NULL, BOOL, UINT8, UINT16, UINT32, UINT64, INT8, INT16, INT32,
+    /// INT64, FLOAT64, CHAR, STRING, BYTES, LIST, MAP, FUNCTION, USERDATA, WEAK.
+    ///
+    /// FLOAT64 values are ordered numerically except that all NaNs are equal to each
+    /// other and greater than every other FLOAT64, including infinity, and -0.0 equals 0.0.
+    /// STRING, BYTES and LIST compare lexicographically, MAP compares its entries sorted by key.
+    /// FUNCTION, USERDATA and WEAK compare by identity.
+    /// ### Example
+    /// ```
+    /// let mut list = vec![
+    ///     shsc::todata!("a"),
+    ///     shsc::todata!(f64::NAN),
+    ///     shsc::todata!(2.5),
+    ///     shsc::Data::NULL,
+    ///     shsc::todata!(3),
+    /// ];
+    /// list.sort();
+    /// let names: Vec<_> = list.iter().map(|d| d.typename()).collect();
+    /// assert_eq!(names, ["NULL", "INT32", "FLOAT64", "FLOAT64", "STRING"]);
+    /// assert!(list[3] == shsc::todata!(f64::NAN));
+    /// ```
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.data, &other.data) {
+            (DataTypes::BOOL(a), DataTypes::BOOL(b)) => a.cmp(b),
+            (DataTypes::UINT8(a), DataTypes::UINT8(b)) => a.cmp(b),
+            (DataTypes::UINT16(a), DataTypes::UINT16(b)) => a.cmp(b),
+            (DataTypes::UINT32(a), DataTypes::UINT32(b)) => a.cmp(b),
+            (DataTypes::UINT64(a), DataTypes::UINT64(b)) => a.cmp(b),
+            (DataTypes::INT8(a), DataTypes::INT8(b)) => a.cmp(b),
+            (DataTypes::INT16(a), DataTypes::INT16(b)) => a.cmp(b),
+            (DataTypes::INT32(a), DataTypes::INT32(b)) => a.cmp(b),
+            (DataTypes::INT64(a), DataTypes::INT64(b)) => a.cmp(b),
+            (DataTypes::FLOAT64(a), DataTypes::FLOAT64(b)) => cmp_f64(*a, *b),
+            (DataTypes::CHAR(a), DataTypes::CHAR(b)) => a.cmp(b),
+            (DataTypes::STRING(a), DataTypes::STRING(b)) => a.cmp(b),
+            (DataTypes::BYTES(a), DataTypes::BYTES(b)) => a.cmp(b),
+            (DataTypes::LIST(a), DataTypes::LIST(b)) => a.cmp(b),
+            (DataTypes::MAP(a), DataTypes::MAP(b)) => a.cmp(b),
+            (DataTypes::FUNCTION(a), DataTypes::FUNCTION(b)) => a.cmp(b),
+            (DataTypes::USERDATA(a), DataTypes::USERDATA(b)) => a.cmp(b),
+            (DataTypes::WEAK(a), DataTypes::WEAK(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl Hash for Data {
+    /// Hash consistent with Eq, so that Data can key std collections.
+    /// ### Example
+    /// ```
+    /// let mut set = std::collections::HashSet::new();
+    /// set.insert(shsc::todata!("a"));
+    /// set.insert(shsc::todata!(0.0));
+    /// assert!(set.contains(&shsc::todata!("a")));
+    /// assert!(set.contains(&shsc::todata!(-0.0)));
+    /// assert!(!set.contains(&shsc::todata!(0)));
+    /// ```
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match &self.data {
+            DataTypes::BOOL(value) => value.hash(state),
+            DataTypes::UINT8(value) => value.hash(state),
+            DataTypes::UINT16(value) => value.hash(state),
+            DataTypes::UINT32(value) => value.hash(state),
+            DataTypes::UINT64(value) => value.hash(state),
+            DataTypes::INT8(value) => value.hash(state),
+            DataTypes::INT16(value) => value.hash(state),
+            DataTypes::INT32(value) => value.hash(state),
+            DataTypes::INT64(value) => value.hash(state),
+            DataTypes::FLOAT64(value) => f64_hash_bits(*value).hash(state),
+            DataTypes::CHAR(value) => value.hash(state),
+            DataTypes::STRING(value) => value.hash(state),
+            DataTypes::BYTES(value) => value.hash(state),
+            DataTypes::LIST(value) => value.hash(state),
+            DataTypes::MAP(value) => value.hash(state),
+            DataTypes::FUNCTION(value) => value.hash(state),
+            DataTypes::USERDATA(value) => value.hash(state),
+            DataTypes::WEAK(value) => value.hash(state),
+            DataTypes::NULL => (),
         }
     }
 }
@@ -0,0 +1,238 @@
+//! Leak tracker for live containers.
+//!
+//! Once tracking is turned on with [`set_tracking`], every shsc::List,
+//! shsc::Map and shsc::String created in a debug build is recorded here, and
+//! forgotten when its last reference is dropped. [`live_objects`] lists what
+//! is still alive, and [`set_report_at_exit`] prints a report of the
+//! survivors to stderr when the process exits.
+//!
+//! Tracking is off by default, since recording takes a global lock and
+//! possibly a backtrace for every container. Creation backtraces are recorded
+//! when backtraces are enabled through the `RUST_BACKTRACE` or
+//! `RUST_LIB_BACKTRACE` environment variables. In release builds of the
+//! library nothing is recorded, see [`is_available`].
+//!
+//! The registry only holds what was known when a container was created, so
+//! it never reads containers that other threads own.
+//!
+//! ### Example
+//! ```
+//! use shsc::traits::RefCopy;
+//! shsc::debug::set_tracking(true);
+//! let mut list = shsc::List::new();
+//! let copy = list.refcopy();
+//! if shsc::debug::is_available() {
+//!     let live = shsc::debug::live_objects();
+//!     assert_eq!(live.len(), 1);
+//!     assert_eq!(live[0].kind, "List");
+//! }
+//! drop(copy);
+//! list.refdrop();
+//! assert!(shsc::debug::live_objects().is_empty());
+//! ```
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, Once};
+
+/// A container that has not been deallocated.
+#[derive(Debug, Clone)]
+pub struct LiveObject {
+    /// "List", "Map" or "String"
+    pub kind: &'static str,
+    /// Where the container was created, empty if backtraces are disabled
+    pub backtrace: std::string::String,
+}
+
+struct Record {
+    kind: &'static str,
+    order: u64,
+    backtrace: Backtrace,
+}
+
+struct Registry {
+    records: BTreeMap<usize, Record>,
+    created: u64,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    records: BTreeMap::new(),
+    created: 0,
+});
+static TRACKING: AtomicBool = AtomicBool::new(false);
+// set once tracking was ever on, so containers recorded then are still forgotten
+static TRACKED: AtomicBool = AtomicBool::new(false);
+static REPORT_AT_EXIT: AtomicBool = AtomicBool::new(false);
+static AT_EXIT: Once = Once::new();
+
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> std::os::raw::c_int;
+}
+
+/// Choose whether containers created from now on are recorded.
+/// Tracking is off by default, and has no effect unless [`is_available`].
+/// ### Arguments
+/// * `enabled` - true to record new containers
+/// ### Example
+/// ```
+/// let before = shsc::List::new();
+/// shsc::debug::set_tracking(true);
+/// let after = shsc::List::new();
+/// shsc::debug::set_tracking(false);
+/// // only the list created while tracking is recorded
+/// assert_eq!(shsc::debug::live_objects().len(), usize::from(shsc::debug::is_available()));
+/// ```
+
+pub fn set_tracking(enabled: bool) {
+    if enabled {
+        TRACKED.store(true, Ordering::Relaxed);
+    }
+    TRACKING.store(enabled, Ordering::Relaxed);
+}
+
+/// Check whether the library was built with leak tracking.
+/// Tracking needs debug assertions in the build of shsc itself, which may
+/// differ from the build of the code calling it.
+/// ### Returns
+/// True if containers are recorded while tracking is on
+/// ### Example
+/// ```
+/// if !shsc::debug::is_available() {
+///     shsc::debug::set_tracking(true);
+///     let _list = shsc::List::new();
+///     assert!(shsc::debug::live_objects().is_empty());
+/// }
+/// ```
+
+pub fn is_available() -> bool {
+    cfg!(debug_assertions)
+}
+
+/// Get every recorded container that is still alive, oldest first.
+/// Always empty unless [`is_available`].
+/// ### Returns
+/// The live containers
+/// ### Example
+/// ```
+/// shsc::debug::set_tracking(true);
+/// let m = shsc::Map::new();
+/// let s = shsc::String::from("hi");
+/// if shsc::debug::is_available() {
+///     let kinds: Vec<_> = shsc::debug::live_objects().iter().map(|o| o.kind).collect();
+///     assert_eq!(kinds, vec!["Map", "String"]);
+/// }
+/// ```
+
+pub fn live_objects() -> Vec<LiveObject> {
+    let registry = lock();
+    let mut records: Vec<&Record> = registry.records.values().collect();
+    records.sort_by_key(|record| record.order);
+    records
+        .into_iter()
+        .map(|record| LiveObject {
+            kind: record.kind,
+            backtrace: match record.backtrace.status() {
+                BacktraceStatus::Captured => record.backtrace.to_string(),
+                _ => std::string::String::new(),
+            },
+        })
+        .collect()
+}
+
+/// Format a report of every recorded container that is still alive.
+/// ### Returns
+/// The report, or an empty string if nothing is alive
+/// ### Example
+/// ```
+/// shsc::debug::set_tracking(true);
+/// let list = shsc::List::new();
+/// if shsc::debug::is_available() {
+///     assert!(shsc::debug::report().contains("  List\n"));
+/// }
+/// drop(list);
+/// assert_eq!(shsc::debug::report(), "");
+/// ```
+
+pub fn report() -> std::string::String {
+    let live = live_objects();
+    if live.is_empty() {
+        return std::string::String::new();
+    }
+    let mut report = format!("shsc: {} live object(s)\n", live.len());
+    for object in live {
+        report.push_str(&format!("  {}\n", object.kind));
+        for line in object.backtrace.lines() {
+            report.push_str(&format!("    {}\n", line));
+        }
+    }
+    report
+}
+
+/// Choose whether the report is printed when the process exits.
+/// It is not printed by default, and only lists containers that were tracked.
+/// ### Arguments
+/// * `enabled` - true to print the report at exit
+/// ### Example
+/// ```
+/// shsc::debug::set_tracking(true);
+/// shsc::debug::set_report_at_exit(true);
+/// let list = shsc::List::new();
+/// if shsc::debug::is_available() {
+///     assert_eq!(shsc::debug::live_objects().len(), 1);
+/// }
+/// drop(list);
+/// // nothing is alive, so nothing is printed at exit
+/// assert!(shsc::debug::live_objects().is_empty());
+/// ```
+
+pub fn set_report_at_exit(enabled: bool) {
+    if enabled {
+        AT_EXIT.call_once(|| unsafe {
+            atexit(report_at_exit);
+        });
+    }
+    REPORT_AT_EXIT.store(enabled, Ordering::Relaxed);
+}
+
+extern "C" fn report_at_exit() {
+    if !REPORT_AT_EXIT.load(Ordering::Relaxed) {
+        return;
+    }
+    let report = report();
+    if !report.is_empty() {
+        eprint!("{}", report);
+    }
+}
+
+/// Record a container created with the reference count at addr.
+pub(crate) fn record(kind: &'static str, addr: usize) {
+    if is_available() && TRACKING.load(Ordering::Relaxed) {
+        let backtrace = Backtrace::capture();
+        let mut registry = lock();
+        registry.created += 1;
+        let order = registry.created;
+        registry.records.insert(
+            addr,
+            Record {
+                kind,
+                order,
+                backtrace,
+            },
+        );
+    }
+}
+
+/// Forget a container whose last reference was dropped.
+pub(crate) fn forget(addr: usize) {
+    if is_available() && TRACKED.load(Ordering::Relaxed) {
+        lock().records.remove(&addr);
+    }
+}
+
+fn lock() -> MutexGuard<'static, Registry> {
+    // a panic while holding the lock leaves the registry consistent
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,280 @@
+use crate::traits::{self, RefC, RefCopy};
+use std::cell::UnsafeCell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+/// Signature of native functions callable through shsc::Data.
+pub type NativeFn = fn(&mut [crate::Data]) -> Result<crate::Data, CallError>;
+
+/// Signature of closures callable through shsc::Data.
+/// The first argument is the captured environment, shared by every call.
+pub type ClosureFn = dyn Fn(&mut crate::List, &mut [crate::Data]) -> Result<crate::Data, CallError>;
+
+/// Error returned when calling shsc::Data fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    /// The called data is not a FUNCTION; holds its typename.
+    NotCallable(std::string::String),
+    /// The function itself reported an error.
+    Failed(std::string::String),
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::NotCallable(typename) => {
+                write!(f, "shsc::Function: {} is not callable", typename)
+            }
+            CallError::Failed(message) => write!(f, "shsc::Function: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+enum Callable {
+    Native(NativeFn),
+    Closure {
+        func: Rc<ClosureFn>,
+        // only borrowed mutably to take a reference copy
+        env: UnsafeCell<crate::List>,
+    },
+}
+
+pub struct Function {
+    func: *mut Callable,
+    refc: *mut i64,
+}
+
+impl Function {
+    /// Create a new shsc::Function struct wrapping a native function.
+    /// ### Arguments
+    /// * `func` - A native function taking the call arguments
+    /// ### Returns
+    /// A new Function struct
+    /// ### Example
+    /// ```
+    /// fn add(args: &mut [shsc::Data]) -> Result<shsc::Data, shsc::function::CallError> {
+    ///     Ok(&args[0] + &args[1])
+    /// }
+    /// let f = shsc::Function::native(add);
+    /// let mut args = [shsc::todata!(1), shsc::todata!(2)];
+    /// assert!(f.call(&mut args).unwrap() == shsc::todata!(3));
+    /// ```
+
+    pub fn native(func: NativeFn) -> Self {
+        Function::from_callable(Callable::Native(func))
+    }
+
+    /// Create a new shsc::Function struct wrapping a closure with a captured environment.
+    /// Every call gets a reference copy of the environment, so changes persist between calls.
+    /// ### Arguments
+    /// * `env` - The captured environment
+    /// * `func` - A closure taking the environment and the call arguments
+    /// ### Returns
+    /// A new Function struct
+    /// ### Example
+    /// ```
+    /// let counter = shsc::Function::closure(
+    ///     shsc::List::from(vec![shsc::todata!(0)]),
+    ///     |env, _args| {
+    ///         env[0] = &env[0] + &shsc::todata!(1);
+    ///         Ok(env[0].clone())
+    ///     },
+    /// );
+    /// counter.call(&mut []).unwrap();
+    /// assert!(counter.call(&mut []).unwrap() == shsc::todata!(2));
+    /// ```
+
+    pub fn closure<F>(env: crate::List, func: F) -> Self
+    where
+        F: Fn(&mut crate::List, &mut [crate::Data]) -> Result<crate::Data, CallError> + 'static,
+    {
+        Function::from_callable(Callable::Closure {
+            func: Rc::new(func),
+            env: UnsafeCell::new(env),
+        })
+    }
+
+    fn from_callable(callable: Callable) -> Self {
+        Function {
+            func: Box::into_raw(Box::new(callable)),
+            refc: Box::into_raw(Box::new(1)),
+        }
+    }
+
+    /// Call the function.
+    /// A function may call itself through a reference copy.
+    /// ### Arguments
+    /// * `args` - The call arguments, which the function may modify
+    /// ### Returns
+    /// The return value, or the CallError reported by the function
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// let f = shsc::Function::native(|args| Ok(shsc::todata!(args.len() as i64)));
+    /// assert!(f.call(&mut [shsc::Data::NULL]).unwrap() == shsc::todata!(1i64));
+    ///
+    /// // factorial, taking itself as the first argument
+    /// let mut fact = shsc::todata!(shsc::Function::closure(shsc::List::new(), |_env, args| {
+    ///     let n = args[1].as_i64().unwrap();
+    ///     if n == 0 {
+    ///         return Ok(shsc::todata!(1i64));
+    ///     }
+    ///     let mut inner = [args[0].refcopy(), shsc::todata!(n - 1)];
+    ///     Ok(&args[1] * &args[0].call(&mut inner)?)
+    /// }));
+    /// let mut args = [fact.refcopy(), shsc::todata!(5i64)];
+    /// assert_eq!(fact.call(&mut args).unwrap().as_i64(), Some(120));
+    /// ```
+
+    pub fn call(&self, args: &mut [crate::Data]) -> Result<crate::Data, CallError> {
+        unsafe {
+            match &*self.func {
+                Callable::Native(func) => func(args),
+                Callable::Closure { func, env } => {
+                    // a fresh handle per call keeps recursive calls from aliasing one another
+                    let func = Rc::clone(func);
+                    let mut env = (*env.get()).refcopy();
+                    func(&mut env, args)
+                }
+            }
+        }
+    }
+
+    /// Get the captured environment of a closure.
+    /// ### Returns
+    /// The environment, or None for native functions
+    /// ### Example
+    /// ```
+    /// let f = shsc::Function::closure(shsc::List::new(), |_env, _args| Ok(shsc::Data::NULL));
+    /// assert_eq!(f.env().unwrap().len(), 0);
+    /// ```
+
+    pub fn env(&self) -> Option<&crate::List> {
+        unsafe {
+            match &*self.func {
+                Callable::Native(_) => None,
+                Callable::Closure { env, .. } => Some(&*env.get()),
+            }
+        }
+    }
+}
+
+impl traits::ToStr for Function {
+    /// Implement the ToStr trait for the Function struct.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let f = shsc::Function::native(|_args| Ok(shsc::Data::NULL));
+    /// assert_eq!(f.tostr(), "<native function>");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        unsafe {
+            match &*self.func {
+                Callable::Native(_) => std::string::String::from("<native function>"),
+                Callable::Closure { .. } => std::string::String::from("<closure>"),
+            }
+        }
+    }
+}
+
+impl traits::RefCopy for Function {
+    /// Implement the RefCopy trait for the Function struct.
+    /// This allows us to create a reference copy of the Function struct.
+    fn refcopy(&mut self) -> Self {
+        self.incrc();
+        Function {
+            func: self.func,
+            refc: self.refc,
+        }
+    }
+
+    fn refdrop(self) {
+        // drop trait is called here
+    }
+}
+
+impl Clone for Function {
+    /// Implement the Clone trait for the Function struct.
+    /// Results in a new Function struct with a reference count of 1.
+    /// A closure shares its code with the original but gets a deep copy of its environment.
+    fn clone(&self) -> Self {
+        unsafe {
+            match &*self.func {
+                Callable::Native(func) => Function::native(*func),
+                Callable::Closure { func, env } => Function::from_callable(Callable::Closure {
+                    func: Rc::clone(func),
+                    env: UnsafeCell::new((*env.get()).clone()),
+                }),
+            }
+        }
+    }
+}
+
+impl PartialEq for Function {
+    /// Implement the PartialEq trait for the Function struct.
+    /// Functions are equal only to reference copies of themselves.
+    fn eq(&self, other: &Self) -> bool {
+        self.func == other.func
+    }
+}
+
+impl Eq for Function {}
+
+impl PartialOrd for Function {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Function {
+    /// Implement the Ord trait for the Function struct.
+    /// Functions are ordered by address, which is stable for the life of the function.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.func as usize).cmp(&(other.func as usize))
+    }
+}
+
+impl Hash for Function {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.func as usize).hash(state);
+    }
+}
+
+impl traits::RefC for Function {
+    fn incrc(&mut self) {
+        unsafe { *self.refc += 1 };
+    }
+
+    fn decrc(&mut self) {
+        unsafe {
+            *self.refc -= 1;
+            if (*self.refc) < 0 {
+                *self.refc = 0;
+            }
+        }
+    }
+
+    fn getrc(&self) -> i64 {
+        unsafe { *self.refc }
+    }
+}
+
+impl Drop for Function {
+    /// Implement the Drop trait for the Function struct.
+    /// The function and its environment are freed when the reference count reaches zero.
+    fn drop(&mut self) {
+        self.decrc();
+        if self.getrc() > 0 {
+            return;
+        }
+        unsafe {
+            drop(Box::from_raw(self.func));
+            drop(Box::from_raw(self.refc));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Opt-in cycle collector for reference counted containers.
+//!
+//! Reference counting alone cannot free a shsc::List or shsc::Map that is
+//! reachable from itself, for example a map holding a reference copy of itself.
+//! While collection is enabled, every List and Map created on the current thread
+//! is registered here, and [`collect`] finds registered containers that are only
+//! referenced by other registered containers using trial deletion:
+//!
+//! 1. each container starts with its reference count
+//! 2. every reference held by another registered container is subtracted
+//! 3. containers left with a positive count are referenced from outside, so they
+//!    and everything reachable from them are kept
+//! 4. the remaining containers are cleared, which breaks the cycles and lets
+//!    reference counting free them
+//!
+//! References held by FUNCTION closures and USERDATA are opaque to the collector,
+//! so containers reachable through them are always kept.
+//!
+//! ### Example
+//! ```
+//! use shsc::traits::RefCopy;
+//! shsc::gc::enable();
+//! let mut m = shsc::Map::new();
+//! let copy = m.refcopy();
+//! m.insert("self", shsc::todata!(copy));
+//! drop(m);
+//! assert_eq!(shsc::gc::collect(), 1);
+//! shsc::gc::disable();
+//! ```
+
+use crate::traits::{RefC, RefCopy};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::mem::ManuallyDrop;
+
+/// A registered container.
+/// Holds a copy of the handle without a reference count, it is removed from
+/// the registry before the container is deallocated.
+enum Node {
+    List(ManuallyDrop<crate::List>),
+    Map(ManuallyDrop<crate::Map>),
+}
+
+/// A strong reference to a container being reclaimed.
+enum Held {
+    List(crate::List),
+    Map(crate::Map),
+}
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static REGISTRY: RefCell<HashMap<usize, Node>> = RefCell::new(HashMap::new());
+}
+
+impl Node {
+    fn shadow(&self) -> Node {
+        match self {
+            Node::List(list) => Node::List(unsafe { list.shadow() }),
+            Node::Map(map) => Node::Map(unsafe { map.shadow() }),
+        }
+    }
+
+    fn getrc(&self) -> i64 {
+        match self {
+            Node::List(list) => list.getrc(),
+            Node::Map(map) => map.getrc(),
+        }
+    }
+
+    /// Call f with the address of every container directly referenced by this one.
+    fn children(&self, mut f: impl FnMut(usize)) {
+        let mut visit = |value: &crate::Data| match &value.data {
+            crate::DataTypes::LIST(list) => f(list.addr()),
+            crate::DataTypes::MAP(map) => f(map.addr()),
+            _ => (),
+        };
+        match self {
+            Node::List(list) => list.iter().for_each(&mut visit),
+            Node::Map(map) => map.values().for_each(&mut visit),
+        }
+    }
+
+    fn hold(&mut self) -> Held {
+        match self {
+            Node::List(list) => Held::List(list.refcopy()),
+            Node::Map(map) => Held::Map(map.refcopy()),
+        }
+    }
+}
+
+impl Held {
+    fn clear(&mut self) {
+        match self {
+            Held::List(list) => list.release(),
+            Held::Map(map) => map.release(),
+        }
+    }
+}
+
+/// Start registering newly created containers on this thread.
+/// Containers created while collection is disabled are never collected.
+/// ### Example
+/// ```
+/// shsc::gc::enable();
+/// assert!(shsc::gc::is_enabled());
+/// shsc::gc::disable();
+/// ```
+
+pub fn enable() {
+    ENABLED.with(|enabled| enabled.set(true));
+}
+
+/// Stop registering newly created containers on this thread.
+/// Containers registered earlier are still collected.
+/// ### Example
+/// ```
+/// shsc::gc::disable();
+/// assert!(!shsc::gc::is_enabled());
+/// ```
+
+pub fn disable() {
+    ENABLED.with(|enabled| enabled.set(false));
+}
+
+/// Check whether newly created containers are registered on this thread.
+/// ### Returns
+/// true if collection is enabled
+/// ### Example
+/// ```
+/// assert!(!shsc::gc::is_enabled());
+/// ```
+
+pub fn is_enabled() -> bool {
+    ENABLED.with(|enabled| enabled.get())
+}
+
+/// Get the number of containers currently registered on this thread.
+/// ### Example
+/// ```
+/// shsc::gc::enable();
+/// let list = shsc::List::new();
+/// assert_eq!(shsc::gc::tracked(), 1);
+/// drop(list);
+/// assert_eq!(shsc::gc::tracked(), 0);
+/// shsc::gc::disable();
+/// ```
+
+pub fn tracked() -> usize {
+    REGISTRY.with(|registry| registry.borrow().len())
+}
+
+/// Free every registered container that is only reachable through cycles.
+/// ### Returns
+/// The number of containers reclaimed
+/// ### Example
+/// ```
+/// use shsc::traits::RefCopy;
+/// shsc::gc::enable();
+/// let mut a = shsc::List::new();
+/// let mut b = shsc::List::new();
+/// a.append(shsc::todata!(b.refcopy()));
+/// b.append(shsc::todata!(a.refcopy()));
+/// let keep = shsc::List::from(vec![shsc::todata!(1)]);
+/// drop(a);
+/// drop(b);
+/// assert_eq!(shsc::gc::collect(), 2);
+/// assert_eq!(shsc::gc::tracked(), 1);
+/// assert_eq!(keep.len(), 1);
+/// shsc::gc::disable();
+/// ```
+
+pub fn collect() -> usize {
+    // snapshot the registry, it must not stay borrowed while containers are dropped
+    let mut nodes: HashMap<usize, Node> = REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .iter()
+            .map(|(&addr, node)| (addr, node.shadow()))
+            .collect()
+    });
+    let mut refs: HashMap<usize, i64> = nodes
+        .iter()
+        .map(|(&addr, node)| (addr, node.getrc()))
+        .collect();
+    for node in nodes.values() {
+        node.children(|child| {
+            if let Some(count) = refs.get_mut(&child) {
+                *count -= 1;
+            }
+        });
+    }
+    let mut reachable = HashSet::new();
+    let mut stack: Vec<usize> = refs
+        .iter()
+        .filter(|(_, &count)| count > 0)
+        .map(|(&addr, _)| addr)
+        .collect();
+    while let Some(addr) = stack.pop() {
+        if !reachable.insert(addr) {
+            continue;
+        }
+        if let Some(node) = nodes.get(&addr) {
+            node.children(|child| {
+                if nodes.contains_key(&child) && !reachable.contains(&child) {
+                    stack.push(child);
+                }
+            });
+        }
+    }
+    // hold every unreachable container so none is freed while the others are cleared
+    let mut held: Vec<Held> = nodes
+        .iter_mut()
+        .filter(|(addr, _)| !reachable.contains(*addr))
+        .map(|(_, node)| node.hold())
+        .collect();
+    for container in held.iter_mut() {
+        container.clear();
+    }
+    held.len()
+}
+
+/// Run f without registering the containers it creates.
+pub(crate) fn suspended<R>(f: impl FnOnce() -> R) -> R {
+    let enabled = is_enabled();
+    disable();
+    let result = f();
+    ENABLED.with(|flag| flag.set(enabled));
+    result
+}
+
+pub(crate) fn track_list(list: &crate::List) {
+    if is_enabled() {
+        let node = Node::List(unsafe { list.shadow() });
+        REGISTRY.with(|registry| registry.borrow_mut().insert(list.addr(), node));
+    }
+}
+
+pub(crate) fn track_map(map: &crate::Map) {
+    if is_enabled() {
+        let node = Node::Map(unsafe { map.shadow() });
+        REGISTRY.with(|registry| registry.borrow_mut().insert(map.addr(), node));
+    }
+}
+
+pub(crate) fn untrack(addr: usize) {
+    // the registry may already be gone while thread locals are destroyed
+    let _ = REGISTRY.try_with(|registry| {
+        if let Ok(mut registry) = registry.try_borrow_mut() {
+            registry.remove(&addr);
+        }
+    });
+}
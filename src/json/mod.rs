@@ -0,0 +1,14 @@
+pub mod parse;
+pub mod ser;
+
+pub use parse::parse;
+pub use parse::ParseError;
+pub use ser::to_string;
+pub use ser::to_string_pretty;
+pub use ser::to_string_with;
+pub use ser::NonFinite;
+pub use ser::Options;
+pub use ser::SerializeError;
+
+/// Maximum nesting depth of arrays and objects, for both reading and writing.
+const MAX_DEPTH: usize = 512;
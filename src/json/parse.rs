@@ -0,0 +1,357 @@
+use std::fmt;
+
+/// Error returned when a JSON document cannot be parsed.
+/// Line and column are 1-based and point at the offending character.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: std::string::String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "shsc::json: {} at line {} column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a JSON document into a shsc::Data tree.
+///
+/// Objects become `MAP`, arrays become `LIST` and strings become `STRING`.
+/// Integers become `INT64`, or `UINT64` if they are too large for `INT64`.
+/// Numbers with a fraction or exponent, and integers too large for `UINT64`, become `FLOAT64`.
+/// `null` becomes `NULL`. If an object repeats a key, the last value wins.
+/// ### Arguments
+/// * `src` - A JSON document
+/// ### Returns
+/// The parsed data, or a ParseError with the line and column of the failure
+/// ### Example
+/// ```
+/// let data = shsc::json::parse(r#"{"name": "shsc", "tags": [1, 2.5, null]}"#).unwrap();
+/// assert_eq!(data.typename(), "MAP");
+/// if let shsc::DataTypes::MAP(map) = &data.data {
+///     assert_eq!(map.get("tags").unwrap().typename(), "LIST");
+/// }
+///
+/// let err = shsc::json::parse("[1,\n  2,]").err().unwrap();
+/// assert_eq!((err.line, err.column), (2, 5));
+/// ```
+
+pub fn parse(src: &str) -> Result<crate::Data, ParseError> {
+    let mut parser = Parser {
+        src: src.as_bytes(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        depth: 0,
+    };
+    parser.skip_whitespace();
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.peek().is_some() {
+        return Err(parser.error("trailing characters after JSON value"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    line: usize,
+    column: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.to_owned(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    /// Consume one byte, keeping line and column in sync.
+    /// Columns count characters, so UTF-8 continuation bytes do not advance them.
+    fn bump(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        if byte == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if byte & 0xC0 != 0x80 {
+            self.column += 1;
+        }
+        Some(byte)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(b) if b == byte => {
+                self.bump();
+                Ok(())
+            }
+            Some(_) => Err(self.error(&format!("expected '{}'", byte as char))),
+            None => Err(self.error(&format!("expected '{}', found end of input", byte as char))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.bump();
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<crate::Data, ParseError> {
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => Ok(crate::todata!(crate::String::from(&self.parse_string()?))),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b't') => self.parse_literal("true", crate::todata!(true)),
+            Some(b'f') => self.parse_literal("false", crate::todata!(false)),
+            Some(b'n') => self.parse_literal("null", crate::Data::NULL),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_literal(&mut self, word: &str, value: crate::Data) -> Result<crate::Data, ParseError> {
+        for &expected in word.as_bytes() {
+            if self.peek() != Some(expected) {
+                return Err(self.error(&format!("invalid literal, expected '{}'", word)));
+            }
+            self.bump();
+        }
+        Ok(value)
+    }
+
+    fn enter(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > super::MAX_DEPTH {
+            return Err(self.error("maximum nesting depth exceeded"));
+        }
+        Ok(())
+    }
+
+    fn parse_array(&mut self) -> Result<crate::Data, ParseError> {
+        self.enter()?;
+        self.expect(b'[')?;
+        let mut list = crate::List::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.bump();
+            self.depth -= 1;
+            return Ok(crate::todata!(list));
+        }
+        loop {
+            self.skip_whitespace();
+            list.append(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => {
+                    self.bump();
+                }
+                Some(b']') => {
+                    self.bump();
+                    break;
+                }
+                Some(_) => return Err(self.error("expected ',' or ']' in array")),
+                None => return Err(self.error("unterminated array")),
+            }
+        }
+        self.depth -= 1;
+        Ok(crate::todata!(list))
+    }
+
+    fn parse_object(&mut self) -> Result<crate::Data, ParseError> {
+        self.enter()?;
+        self.expect(b'{')?;
+        let mut map = crate::Map::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.bump();
+            self.depth -= 1;
+            return Ok(crate::todata!(map));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected string key in object"));
+            }
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.skip_whitespace();
+            let value = self.parse_value()?;
+            map.insert(&key, value);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => {
+                    self.bump();
+                }
+                Some(b'}') => {
+                    self.bump();
+                    break;
+                }
+                Some(_) => return Err(self.error("expected ',' or '}' in object")),
+                None => return Err(self.error("unterminated object")),
+            }
+        }
+        self.depth -= 1;
+        Ok(crate::todata!(map))
+    }
+
+    fn parse_string(&mut self) -> Result<std::string::String, ParseError> {
+        self.expect(b'"')?;
+        let mut bytes: Vec<u8> = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.bump();
+                    break;
+                }
+                Some(b'\\') => {
+                    self.bump();
+                    let c = self.parse_escape()?;
+                    let mut buf = [0u8; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(0x00..=0x1F) => {
+                    return Err(self.error("control character in string"));
+                }
+                Some(byte) => {
+                    self.bump();
+                    bytes.push(byte);
+                }
+            }
+        }
+        // input is a &str and escapes are encoded as UTF-8, so this cannot fail
+        Ok(std::string::String::from_utf8(bytes).expect("shsc::json: invalid UTF-8 in string"))
+    }
+
+    fn parse_escape(&mut self) -> Result<char, ParseError> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{0008}',
+            Some(b'f') => '\u{000C}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.bump();
+                return self.parse_unicode_escape();
+            }
+            Some(_) => return Err(self.error("invalid escape sequence")),
+            None => return Err(self.error("unterminated string")),
+        };
+        self.bump();
+        Ok(c)
+    }
+
+    /// Parse the XXXX of a \uXXXX escape, combining UTF-16 surrogate pairs.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.parse_hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high)
+                .ok_or_else(|| self.error("unpaired surrogate in \\u escape"));
+        }
+        if self.peek() != Some(b'\\') {
+            return Err(self.error("unpaired surrogate in \\u escape"));
+        }
+        self.bump();
+        if self.peek() != Some(b'u') {
+            return Err(self.error("unpaired surrogate in \\u escape"));
+        }
+        self.bump();
+        let low = self.parse_hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("invalid low surrogate in \\u escape"));
+        }
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = match self.peek() {
+                Some(b) if b.is_ascii_hexdigit() => (b as char).to_digit(16).unwrap(),
+                _ => return Err(self.error("expected 4 hex digits in \\u escape")),
+            };
+            self.bump();
+            value = value * 16 + digit;
+        }
+        Ok(value)
+    }
+
+    fn parse_number(&mut self) -> Result<crate::Data, ParseError> {
+        let start = self.pos;
+        let mut integral = true;
+        if self.peek() == Some(b'-') {
+            self.bump();
+        }
+        match self.peek() {
+            Some(b'0') => {
+                self.bump();
+                if let Some(b'0'..=b'9') = self.peek() {
+                    return Err(self.error("leading zeros are not allowed"));
+                }
+            }
+            Some(b'1'..=b'9') => self.skip_digits(),
+            _ => return Err(self.error("expected digit")),
+        }
+        if self.peek() == Some(b'.') {
+            integral = false;
+            self.bump();
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error("expected digit after decimal point"));
+            }
+            self.skip_digits();
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            integral = false;
+            self.bump();
+            if let Some(b'+' | b'-') = self.peek() {
+                self.bump();
+            }
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error("expected digit in exponent"));
+            }
+            self.skip_digits();
+        }
+        // the scanned bytes are all ASCII
+        let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap();
+        if integral {
+            if let Ok(value) = text.parse::<i64>() {
+                return Ok(crate::todata!(value));
+            }
+            if let Ok(value) = text.parse::<u64>() {
+                return Ok(crate::todata!(value));
+            }
+        }
+        match text.parse::<f64>() {
+            Ok(value) => Ok(crate::todata!(value)),
+            Err(_) => Err(self.error("invalid number")),
+        }
+    }
+
+    fn skip_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.bump();
+        }
+    }
+}
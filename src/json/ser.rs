@@ -0,0 +1,304 @@
+use crate::traits::ToStr;
+use std::fmt;
+use std::fmt::Write;
+
+/// Policy for FLOAT64 values that are NaN or infinite, which JSON cannot represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Fail with SerializeError::NonFinite.
+    Error,
+    /// Write `null` in place of the value.
+    Null,
+    /// Write the strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    String,
+}
+
+/// Options controlling JSON output.
+/// ### Example
+/// ```
+/// let options = shsc::json::Options {
+///     indent: Some(4),
+///     sort_keys: true,
+///     ..shsc::json::Options::default()
+/// };
+/// let data = shsc::json::parse(r#"{"b": 1, "a": [true]}"#).unwrap();
+/// let s = shsc::json::to_string_with(&data, &options).unwrap();
+/// assert_eq!(s, "{\n    \"a\": [\n        true\n    ],\n    \"b\": 1\n}");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Spaces per nesting level, or None for compact output.
+    pub indent: Option<usize>,
+    /// Write map keys in sorted order instead of index map order.
+    pub sort_keys: bool,
+    /// What to do with NaN and infinite FLOAT64 values.
+    pub non_finite: NonFinite,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            indent: None,
+            sort_keys: false,
+            non_finite: NonFinite::Error,
+        }
+    }
+}
+
+/// Error returned when a shsc::Data tree cannot be written as JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SerializeError {
+    /// A FLOAT64 was NaN or infinite under the NonFinite::Error policy.
+    NonFinite(f64),
+    /// Nesting went deeper than the writer allows, usually because of a reference cycle.
+    DepthLimitExceeded,
+    /// The data has no JSON representation; holds its typename.
+    Unsupported(std::string::String),
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializeError::NonFinite(value) => {
+                write!(
+                    f,
+                    "shsc::json: cannot serialize non-finite number {}",
+                    value
+                )
+            }
+            SerializeError::DepthLimitExceeded => {
+                write!(f, "shsc::json: maximum nesting depth exceeded")
+            }
+            SerializeError::Unsupported(typename) => {
+                write!(f, "shsc::json: cannot serialize {}", typename)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+/// Write a shsc::Data tree as compact JSON.
+/// Strings and keys are quoted and escaped, CHAR is written as a one character string,
+/// BYTES as a base64 string and NULL as `null`.
+/// Non-finite FLOAT64 values, FUNCTION, USERDATA and WEAK are an error.
+/// ### Arguments
+/// * `data` - The data to serialize
+/// ### Returns
+/// The JSON text
+/// ### Example
+/// ```
+/// let mut m = shsc::Map::new();
+/// m.insert("say", shsc::todata!("\"hi\"\n"));
+/// m.insert("none", shsc::Data::NULL);
+/// let data = shsc::todata!(vec![shsc::todata!(m), shsc::todata!(1.0)]);
+/// let s = shsc::json::to_string(&data).unwrap();
+/// assert!(s == r#"[{"say":"\"hi\"\n","none":null},1.0]"#
+///     || s == r#"[{"none":null,"say":"\"hi\"\n"},1.0]"#);
+/// assert!(shsc::json::to_string(&shsc::todata!(f64::NAN)).is_err());
+/// ```
+
+pub fn to_string(data: &crate::Data) -> Result<std::string::String, SerializeError> {
+    to_string_with(data, &Options::default())
+}
+
+/// Write a shsc::Data tree as JSON indented by two spaces per level.
+/// ### Arguments
+/// * `data` - The data to serialize
+/// ### Returns
+/// The JSON text
+/// ### Example
+/// ```
+/// let data = shsc::json::parse("[1, {}, []]").unwrap();
+/// let s = shsc::json::to_string_pretty(&data).unwrap();
+/// assert_eq!(s, "[\n  1,\n  {},\n  []\n]");
+/// ```
+
+pub fn to_string_pretty(data: &crate::Data) -> Result<std::string::String, SerializeError> {
+    let options = Options {
+        indent: Some(2),
+        ..Options::default()
+    };
+    to_string_with(data, &options)
+}
+
+/// Write a shsc::Data tree as JSON using the given options.
+/// ### Arguments
+/// * `data` - The data to serialize
+/// * `options` - Indentation, key order and non-finite number policy
+/// ### Returns
+/// The JSON text
+/// ### Example
+/// ```
+/// let options = shsc::json::Options {
+///     non_finite: shsc::json::NonFinite::Null,
+///     ..shsc::json::Options::default()
+/// };
+/// let data = shsc::todata!(vec![shsc::todata!(f64::INFINITY), shsc::todata!('x')]);
+/// assert_eq!(shsc::json::to_string_with(&data, &options).unwrap(), r#"[null,"x"]"#);
+/// ```
+
+pub fn to_string_with(
+    data: &crate::Data,
+    options: &Options,
+) -> Result<std::string::String, SerializeError> {
+    let mut writer = Writer {
+        out: std::string::String::new(),
+        options,
+        depth: 0,
+    };
+    writer.write_value(data)?;
+    Ok(writer.out)
+}
+
+struct Writer<'a> {
+    out: std::string::String,
+    options: &'a Options,
+    depth: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn write_value(&mut self, data: &crate::Data) -> Result<(), SerializeError> {
+        match &data.data {
+            crate::DataTypes::BOOL(value) => self.push_display(value),
+            crate::DataTypes::UINT8(value) => self.push_display(value),
+            crate::DataTypes::UINT16(value) => self.push_display(value),
+            crate::DataTypes::UINT32(value) => self.push_display(value),
+            crate::DataTypes::UINT64(value) => self.push_display(value),
+            crate::DataTypes::INT8(value) => self.push_display(value),
+            crate::DataTypes::INT16(value) => self.push_display(value),
+            crate::DataTypes::INT32(value) => self.push_display(value),
+            crate::DataTypes::INT64(value) => self.push_display(value),
+            crate::DataTypes::FLOAT64(value) => return self.write_float(*value),
+            crate::DataTypes::CHAR(value) => {
+                let mut buf = [0u8; 4];
+                self.write_str(value.encode_utf8(&mut buf));
+            }
+            crate::DataTypes::STRING(value) => self.write_str(&value.tostr()),
+            crate::DataTypes::BYTES(value) => self.write_str(&value.tobase64()),
+            crate::DataTypes::LIST(value) => return self.write_list(value),
+            crate::DataTypes::MAP(value) => return self.write_map(value),
+            crate::DataTypes::NULL => self.out.push_str("null"),
+            crate::DataTypes::FUNCTION(_)
+            | crate::DataTypes::USERDATA(_)
+            | crate::DataTypes::WEAK(_) => {
+                return Err(SerializeError::Unsupported(data.typename()));
+            }
+        }
+        Ok(())
+    }
+
+    fn push_display<T: fmt::Display>(&mut self, value: T) {
+        // writing to a String cannot fail
+        let _ = write!(self.out, "{}", value);
+    }
+
+    fn write_float(&mut self, value: f64) -> Result<(), SerializeError> {
+        if value.is_finite() {
+            // Debug keeps a trailing .0 and uses exponents, so the value reads back as FLOAT64
+            let _ = write!(self.out, "{:?}", value);
+            return Ok(());
+        }
+        match self.options.non_finite {
+            NonFinite::Error => return Err(SerializeError::NonFinite(value)),
+            NonFinite::Null => self.out.push_str("null"),
+            NonFinite::String => {
+                if value.is_nan() {
+                    self.write_str("NaN");
+                } else if value > 0.0 {
+                    self.write_str("Infinity");
+                } else {
+                    self.write_str("-Infinity");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.out.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                '\u{0008}' => self.out.push_str("\\b"),
+                '\u{000C}' => self.out.push_str("\\f"),
+                c if (c as u32) < 0x20 => {
+                    let _ = write!(self.out, "\\u{:04x}", c as u32);
+                }
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+
+    fn enter(&mut self) -> Result<(), SerializeError> {
+        self.depth += 1;
+        if self.depth > super::MAX_DEPTH {
+            return Err(SerializeError::DepthLimitExceeded);
+        }
+        Ok(())
+    }
+
+    /// Start a new line at the current depth when pretty printing.
+    fn newline(&mut self) {
+        if let Some(indent) = self.options.indent {
+            self.out.push('\n');
+            for _ in 0..indent * self.depth {
+                self.out.push(' ');
+            }
+        }
+    }
+
+    fn write_list(&mut self, list: &crate::List) -> Result<(), SerializeError> {
+        if list.len() == 0 {
+            self.out.push_str("[]");
+            return Ok(());
+        }
+        self.enter()?;
+        self.out.push('[');
+        for (i, item) in list.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.newline();
+            self.write_value(item)?;
+        }
+        self.depth -= 1;
+        self.newline();
+        self.out.push(']');
+        Ok(())
+    }
+
+    fn write_map(&mut self, map: &crate::Map) -> Result<(), SerializeError> {
+        let mut entries = map.entries();
+        if entries.is_empty() {
+            self.out.push_str("{}");
+            return Ok(());
+        }
+        if self.options.sort_keys {
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        self.enter()?;
+        self.out.push('{');
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.newline();
+            self.write_str(key);
+            self.out.push(':');
+            if self.options.indent.is_some() {
+                self.out.push(' ');
+            }
+            self.write_value(value)?;
+        }
+        self.depth -= 1;
+        self.newline();
+        self.out.push('}');
+        Ok(())
+    }
+}
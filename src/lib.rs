@@ -1,13 +1,42 @@
 // lib.rs
+#![allow(
+    clippy::empty_line_after_doc_comments,
+    clippy::expect_fun_call,
+    clippy::len_without_is_empty,
+    clippy::let_and_return,
+    clippy::new_without_default
+)]
+
+pub mod arith;
+pub mod bytes;
+#[cfg(feature = "compact")]
+pub mod compact;
 pub mod data;
+pub mod debug;
+pub mod function;
+pub mod gc;
 pub mod string;
+pub mod symbol;
+pub mod sync;
 pub mod list;
 pub mod map;
+#[cfg(feature = "serde")]
+pub mod serde_impl;
+pub mod json;
 pub mod traits;
+pub mod userdata;
 pub mod util;
+pub mod weak;
 
 pub use data::Data;
 pub use string::String;
 pub use list::List;
 pub use map::Map;
+pub use symbol::Symbol;
+pub use bytes::Bytes;
+pub use function::Function;
+pub use userdata::UserData;
+pub use weak::Weak;
 pub use data::DataTypes;
+#[cfg(feature = "serde")]
+pub use serde_impl::{from_data, to_data};
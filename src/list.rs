@@ -1,15 +1,42 @@
 use crate::traits::{self, RefC, RefCopy};
-use crate::util::alloc;
-use std::{ops, ptr};
+use crate::util::alloc::{self, AllocError};
+use crate::{debug, gc};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::{self, Bound, RangeBounds};
+use std::ptr;
 
+/// Row size of lists created without with_row_size.
 const DEFAULT_COLS: usize = 32;
 
 pub struct List {
-    list: *mut *mut crate::Data,
+    list: *mut *mut *mut crate::Data,
     len: *mut usize,
     rows: *mut usize,
     cols: *mut usize,
+    reserved: *mut usize,
+    first: *mut usize,
+    head: *mut usize,
+    heap: *mut alloc::Heap,
     refc: *mut i64,
+    weak: *mut i64,
+}
+
+/// A non-owning handle to a shsc::List.
+/// Does not hold a strong reference, so it does not keep the list alive.
+pub struct Weak {
+    list: *mut *mut *mut crate::Data,
+    len: *mut usize,
+    rows: *mut usize,
+    cols: *mut usize,
+    reserved: *mut usize,
+    first: *mut usize,
+    head: *mut usize,
+    heap: *mut alloc::Heap,
+    refc: *mut i64,
+    weak: *mut i64,
 }
 
 impl List {
@@ -22,12 +49,26 @@ impl List {
     /// ```
 
     pub fn new() -> Self {
+        let list = List::untracked();
+        gc::track_list(&list);
+        debug::record("List", list.addr());
+        list
+    }
+
+    /// Create a new list that is never registered with the cycle collector.
+    /// Used for the internal storage of String and Map.
+    pub(crate) fn untracked() -> Self {
         let list = List {
-            list: ptr::null_mut(),
+            list: Box::into_raw(Box::new(ptr::null_mut())),
             len: Box::into_raw(Box::new(0)),
             rows: Box::into_raw(Box::new(0)),
             cols: Box::into_raw(Box::new(DEFAULT_COLS)),
+            reserved: Box::into_raw(Box::new(0)),
+            first: Box::into_raw(Box::new(0)),
+            head: Box::into_raw(Box::new(0)),
+            heap: Box::into_raw(Box::new(alloc::current())),
             refc: Box::into_raw(Box::new(1)),
+            weak: Box::into_raw(Box::new(0)),
         };
         list
     }
@@ -54,6 +95,51 @@ impl List {
         list
     }
 
+    /// Create a new list with room for a number of items
+    /// ### Arguments
+    /// * `capacity` - Number of items the list can hold before adding rows
+    /// ### Returns
+    /// A new list
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::with_capacity(100);
+    /// assert!(list.capacity() >= 100);
+    ///
+    /// // removals keep the room asked for
+    /// list.append(shsc::todata!(1));
+    /// list.pop_back();
+    /// assert!(list.capacity() >= 100);
+    /// ```
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut list = List::new();
+        list.reserve(capacity);
+        list
+    }
+
+    /// Create a new list that allocates rows of a given number of items.
+    /// Small rows waste less memory in small lists,
+    /// large rows make iteration over big lists faster.
+    /// ### Arguments
+    /// * `size` - Number of items in each row
+    /// ### Returns
+    /// A new list
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::with_row_size(4);
+    /// list.append(shsc::todata!(1));
+    /// assert_eq!(list.capacity(), 4);
+    /// ```
+
+    pub fn with_row_size(size: usize) -> Self {
+        if size == 0 {
+            panic!("shsc::List: with_row_size: row size must be non-zero");
+        }
+        let list = List::new();
+        unsafe { *list.cols = size };
+        list
+    }
+
     /// Append data to the list
     /// ### Arguments
     /// * `data` - Data to append
@@ -68,26 +154,40 @@ impl List {
     pub fn append(&mut self, data: crate::Data) {
         unsafe {
             // if list has reached capacity, add new rows
-            if (*self.len) >= (*self.rows) * (*self.cols) {
-                // reallocate list
-                self.list = alloc::reallocate::<*mut crate::Data>(self.list, (*self.rows) + 1);
-                *self.rows += 1;
-                // allocate new row
-                let dest = self.list.add((*self.rows) - 1);
-                let newrow = alloc::allocate::<crate::Data>(*self.cols);
-                dest.write(newrow);
-            }
-            // else put data in list[len / rows][len % cols]
-            let row = (*self.len) / (*self.cols);
-            let col = (*self.len) % (*self.cols);
-            // write data to list
-            let dest = (*self.list.add(row)).add(col);
-            dest.write(data);
+            self.grow();
+            // write data to the slot after the last item
+            self.slot(*self.len).write(data);
             // increment length
             *self.len += 1;
         }
     }
 
+    /// Append data to the list, reporting allocation failure instead of aborting
+    /// ### Arguments
+    /// * `data` - Data to append, dropped if it cannot be stored
+    /// ### Returns
+    /// An error if the list could not grow
+    /// ### Example
+    /// ```
+    /// use std::rc::Rc;
+    /// use shsc::util::alloc::{self, Metered};
+    /// let heap = Rc::new(Metered::new());
+    /// heap.set_limit(Some(4096));
+    /// let mut list = alloc::with_allocator(heap.clone(), shsc::List::new);
+    /// let mut result = Ok(());
+    /// while result.is_ok() {
+    ///     result = list.try_append(shsc::todata!(0));
+    /// }
+    /// assert!(heap.used() <= 4096);
+    /// assert_eq!(heap.failures(), 1);
+    /// ```
+
+    pub fn try_append(&mut self, data: crate::Data) -> Result<(), AllocError> {
+        self.try_make_room(1)?;
+        self.append(data);
+        Ok(())
+    }
+
     /// Get data at index
     /// ### Arguments
     /// * `index` - Index of data
@@ -108,10 +208,7 @@ impl List {
             if index >= (*self.len) {
                 return None;
             }
-            let row = index / (*self.cols);
-            let col = index % (*self.cols);
-            let src = (*self.list.add(row)).add(col);
-            Some(&*src)
+            Some(&*self.slot(index))
         }
     }
 
@@ -135,10 +232,7 @@ impl List {
             if index >= (*self.len) {
                 return None;
             }
-            let row = index / (*self.cols);
-            let col = index % (*self.cols);
-            let src = (*self.list.add(row)).add(col);
-            Some(&mut *src)
+            Some(&mut *self.slot(index))
         }
     }
 
@@ -162,13 +256,7 @@ impl List {
             if index >= (*self.len) {
                 return None;
             }
-            let row = index / (*self.cols);
-            let col = index % (*self.cols);
-            let src = (*self.list.add(row)).add(col);
-            let tmp = (*src).refcopy();
-            src.read().refdrop();
-            *src = crate::Data::NULL;
-            Some(tmp)
+            Some(ptr::replace(self.slot(index), crate::Data::NULL))
         }
     }
 
@@ -189,7 +277,82 @@ impl List {
         unsafe { *self.len }
     }
 
+    /// Get the number of items the list can hold before it adds rows
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::with_row_size(8);
+    /// assert_eq!(list.capacity(), 0);
+    /// list.append(shsc::todata!(1));
+    /// assert_eq!(list.capacity(), 8);
+    /// ```
+
+    pub fn capacity(&self) -> usize {
+        unsafe { (*self.rows) * (*self.cols) - (*self.head) }
+    }
+
+    /// Add rows until there is room for additional more items.
+    /// Removals keep the room reserved, until shrink_to_fit.
+    /// ### Arguments
+    /// * `additional` - Number of items to make room for
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::from(vec![shsc::todata!(1)]);
+    /// list.reserve(100);
+    /// assert!(list.capacity() >= 101);
+    /// ```
+
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(err) = self.try_reserve(additional) {
+            err.abort();
+        }
+    }
+
+    /// Add rows until there is room for additional more items, reporting allocation failure.
+    /// On failure the list is left as it was.
+    /// ### Arguments
+    /// * `additional` - Number of items to make room for
+    /// ### Returns
+    /// An error if the list could not grow
+    /// ### Example
+    /// ```
+    /// use std::rc::Rc;
+    /// use shsc::util::alloc::{self, Metered};
+    /// let heap = Rc::new(Metered::new());
+    /// heap.set_limit(Some(4096));
+    /// let mut list = alloc::with_allocator(heap.clone(), shsc::List::new);
+    /// assert!(list.try_reserve(10).is_ok());
+    /// assert!(list.try_reserve(1_000_000).is_err());
+    /// ```
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.try_make_room(additional)?;
+        unsafe { *self.reserved = (*self.reserved).max((*self.len) + additional) };
+        Ok(())
+    }
+
+    /// Return every row not holding items to the allocator, dropping any reserved room.
+    /// Rows are also returned automatically once removals leave most of them unused,
+    /// but never below the room reserved with with_capacity or reserve.
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::with_capacity(1000);
+    /// list.append(shsc::todata!(1));
+    /// list.shrink_to_fit();
+    /// assert_eq!(list.capacity(), 32);
+    /// ```
+
+    pub fn shrink_to_fit(&mut self) {
+        unsafe {
+            if (*self.len) == 0 {
+                *self.head = 0;
+            }
+            *self.reserved = 0;
+            self.release_rows(self.rows_in_use());
+        }
+    }
+
     /// Get list as vector
+    /// Allocates a new vector, use iter to walk the list without copying
     /// ### Returns
     /// List as vector
     /// ### Example
@@ -206,16 +369,14 @@ impl List {
         unsafe {
             let mut vec: Vec<&crate::Data> = Vec::new();
             for i in 0..(*self.len) {
-                let row = i / (*self.cols);
-                let col = i % (*self.cols);
-                let src = (*self.list.add(row)).add(col);
-                vec.push(&*src);
+                vec.push(&*self.slot(i));
             }
             vec
         }
     }
 
     /// Get list as vector of mutable data
+    /// Allocates a new vector, use iter_mut to walk the list without copying
     /// ### Returns
     /// List as vector of mutable data
     /// ### Example
@@ -232,15 +393,54 @@ impl List {
         unsafe {
             let mut vec = Vec::new();
             for i in 0..(*self.len) {
-                let row = i / (*self.cols);
-                let col = i % (*self.cols);
-                let src = (*self.list.add(row)).add(col);
-                vec.push(&mut *src);
+                vec.push(&mut *self.slot(i));
             }
             vec
         }
     }
 
+    /// Iterate over the list without copying it
+    /// ### Returns
+    /// An iterator over references to the data
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(3),
+    /// ]);
+    /// let sum: i64 = list.iter().filter_map(|d| d.as_i64()).sum();
+    /// assert_eq!(sum, 6);
+    /// assert_eq!(list.iter().rev().next().unwrap().as_i64(), Some(3));
+    /// ```
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            list: self,
+            cursor: Cursor::new(0, self.len()),
+        }
+    }
+
+    /// Iterate over the list, allowing the data to be modified
+    /// ### Returns
+    /// An iterator over mutable references to the data
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// for item in list.iter_mut() {
+    ///     *item = shsc::todata!(item.as_i64().unwrap() * 10);
+    /// }
+    /// assert_eq!(list[1].as_i64(), Some(20));
+    /// ```
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            cursor: Cursor::new(0, self.len()),
+            list: self,
+            marker: PhantomData,
+        }
+    }
+
     /// Insert data at index
     /// ### Arguments
     /// * `index` - Index to insert data
@@ -260,36 +460,43 @@ impl List {
             if index > (*self.len) {
                 panic!("shsc::List: insert: index out of bounds for {}", index);
             }
-            // if list has reached capacity, add new rows
-            if (*self.len) >= (*self.rows) * (*self.cols) {
-                // reallocate list
-                self.list = alloc::reallocate::<*mut crate::Data>(self.list, (*self.rows) + 1);
-                *self.rows += 1;
-                // allocate new row
-                let dest = self.list.add((*self.rows) - 1);
-                let newrow = alloc::allocate::<crate::Data>(*self.cols);
-                dest.write(newrow);
-            }
-            // shift elements to the right
-            for i in (index..(*self.len)).rev() {
-                let row = i / (*self.cols);
-                let col = i % (*self.cols);
-                let row1 = (i + 1) / (*self.cols);
-                let col1 = (i + 1) % (*self.cols);
-                let src = (*self.list.add(row)).add(col);
-                let dest = (*self.list.add(row1)).add(col1);
-                *dest = (*src).refcopy();
+            if index < (*self.len) - index {
+                // shift the elements before index to the left
+                self.grow_front();
+                self.shift_head_back();
+                *self.len += 1;
+                List::move_block(self, 1, self, 0, index);
+            } else {
+                // if list has reached capacity, add new rows
+                self.grow();
+                // shift elements to the right
+                List::move_block(self, index, self, index + 1, (*self.len) - index);
+                *self.len += 1;
             }
             // insert data at index
-            let row = index / (*self.cols);
-            let col = index % (*self.cols);
-            let dest = (*self.list.add(row)).add(col);
-            dest.write(data);
-            // increment length
-            *self.len += 1;
+            self.slot(index).write(data);
         }
     }
 
+    /// Insert data at the specified index, reporting allocation failure instead of aborting
+    /// ### Arguments
+    /// * `index` - Index to insert data at
+    /// * `data` - Data to insert, dropped if it cannot be stored
+    /// ### Returns
+    /// An error if the list could not grow
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::from(vec![shsc::todata!(2)]);
+    /// list.try_insert(0, shsc::todata!(1)).unwrap();
+    /// assert_eq!(list[0].as_i64(), Some(1));
+    /// ```
+
+    pub fn try_insert(&mut self, index: usize, data: crate::Data) -> Result<(), AllocError> {
+        self.try_make_room(1)?;
+        self.insert(index, data);
+        Ok(())
+    }
+
     /// Remove data at index
     /// ### Arguments
     /// * `index` - Index to remove data
@@ -308,205 +515,1451 @@ impl List {
             if index >= (*self.len) {
                 panic!("shsc::List: remove: index out of bounds for {}", index);
             }
-            let removed =
-                (*(*self.list.add(index / (*self.cols))).add(index % (*self.cols))).refcopy();
-            // shift elements to the left
-            for i in index..(*self.len) - 1 {
-                let row = i / (*self.cols);
-                let col = i % (*self.cols);
-                let row1 = (i + 1) / (*self.cols);
-                let col1 = (i + 1) % (*self.cols);
-                let src = (*self.list.add(row1)).add(col1);
-                let dest = (*self.list.add(row)).add(col);
-                dest.read().refdrop();
-                *dest = (*src).refcopy();
+            let removed = self.slot(index).read();
+            if index < (*self.len) - index - 1 {
+                // shift the elements before index to the right
+                List::move_block(self, 0, self, 1, index);
+                self.shift_head_forward();
+            } else {
+                // shift elements to the left
+                List::move_block(self, index + 1, self, index, (*self.len) - index - 1);
             }
             // decrement length
             *self.len -= 1;
+            self.trim();
             removed
         }
     }
-}
-
-impl ops::Index<usize> for List {
-    type Output = crate::Data;
 
-    /// Implement the Index trait for the List struct.
-    /// This allows us to index the List struct.
+    /// Add data to the front of the list.
+    /// The list can grow at either end in amortized constant time,
+    /// so it can be used as a stack or a queue.
     /// ### Arguments
-    /// * `index` - Index of data
-    /// ### Returns
-    /// Data at index
+    /// * `data` - Data to add
     /// ### Example
     /// ```
-    /// let list = shsc::List::from(vec![
-    ///     shsc::todata!(1),
-    ///     shsc::todata!(2),
-    ///     shsc::todata!(3),
-    /// ]);
-    /// let data = &list[1];
+    /// use shsc::traits::ToStr;
+    /// let mut list = shsc::List::from(vec![shsc::todata!(2)]);
+    /// list.push_front(shsc::todata!(1));
+    /// list.push_front(shsc::todata!(0));
+    /// assert_eq!(list.tostr(), "[0,1,2]");
     /// ```
 
-    fn index(&self, index: usize) -> &crate::Data {
-        self.get(index)
-            .expect(&format!("shsc::List: index: undefined data at {}", index))
+    pub fn push_front(&mut self, data: crate::Data) {
+        unsafe {
+            self.grow_front();
+            self.shift_head_back();
+            *self.len += 1;
+            self.slot(0).write(data);
+        }
     }
-}
 
-impl ops::IndexMut<usize> for List {
-    /// Implement the IndexMut trait for the List struct.
-    /// This allows us to index the List struct and modify the data at the index.
-    /// ### Arguments
-    /// * `index` - Index of data
+    /// Remove the last item of the list
     /// ### Returns
-    /// Mutable data at index
+    /// The last item, or None if the list is empty
     /// ### Example
     /// ```
-    /// let mut list = shsc::List::from(vec![
-    ///     shsc::todata!(1),
-    ///     shsc::todata!(2),
-    ///     shsc::todata!(3),
-    /// ]);
-    /// list[1] = shsc::todata!(4);
+    /// let mut list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// assert_eq!(list.pop_back().unwrap().as_i64(), Some(2));
+    /// assert_eq!(list.pop_back().unwrap().as_i64(), Some(1));
+    /// assert!(list.pop_back().is_none());
     /// ```
 
-    fn index_mut(&mut self, index: usize) -> &mut crate::Data {
-        self.get_mut(index).expect(&format!(
-            "shsc::List: index_mut: undefined data at {}",
-            index
-        ))
+    pub fn pop_back(&mut self) -> Option<crate::Data> {
+        unsafe {
+            if (*self.len) == 0 {
+                return None;
+            }
+            *self.len -= 1;
+            let item = self.slot(*self.len).read();
+            self.trim();
+            Some(item)
+        }
     }
-}
 
-impl traits::ToStr for List {
-    /// Get list as string
+    /// Remove the first item of the list
     /// ### Returns
-    /// List as string
+    /// The first item, or None if the list is empty
     /// ### Example
     /// ```
-    /// use crate::shsc::traits::ToStr;
-    /// let list = shsc::List::from(vec![
-    ///     shsc::todata!(1),
-    ///     shsc::todata!(2),
-    ///     shsc::todata!(3),
-    /// ]);
-    /// let s = list.tostr();
+    /// let mut queue = shsc::List::new();
+    /// for i in 0..100 {
+    ///     queue.append(shsc::todata!(i));
+    /// }
+    /// let mut expected = 0;
+    /// while let Some(item) = queue.pop_front() {
+    ///     assert_eq!(item.as_i64(), Some(expected));
+    ///     expected += 1;
+    /// }
+    /// assert_eq!(expected, 100);
     /// ```
 
-    fn tostr(&self) -> std::string::String {
-        let mut s = String::new();
-        s.push('[');
-        for (i, item) in self.as_vec().iter().enumerate() {
-            s.push_str(&item.tostr());
-            if i < self.len() - 1 {
-                s.push(',');
+    pub fn pop_front(&mut self) -> Option<crate::Data> {
+        unsafe {
+            if (*self.len) == 0 {
+                return None;
             }
+            let item = self.slot(0).read();
+            self.shift_head_forward();
+            *self.len -= 1;
+            self.trim();
+            Some(item)
         }
-        s.push(']');
-        s
     }
-}
 
-impl traits::RefCopy for List {
-    /// Implement the RefCopy trait for the List struct.
-    /// This allows us to create a reference copy of the List struct.
+    /// Get the first item of the list
     /// ### Returns
-    /// A reference copy of the list
+    /// The first item, or None if the list is empty
     /// ### Example
     /// ```
-    /// use crate::shsc::traits::RefCopy;
-    /// let mut list = shsc::List::from(vec![
-    ///     shsc::todata!(1),
-    ///     shsc::todata!(2),
-    ///     shsc::todata!(3),
-    /// ]);
-    /// let list1 = list.refcopy();
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// assert_eq!(list.peek_front().unwrap().as_i64(), Some(1));
     /// ```
 
-    fn refcopy(&mut self) -> Self {
-        self.incrc();
-        List {
-            list: self.list,
-            len: self.len,
-            rows: self.rows,
-            cols: self.cols,
-            refc: self.refc,
-        }
+    pub fn peek_front(&self) -> Option<&crate::Data> {
+        self.get(0)
     }
 
-    /// Implement the RefDrop trait for the List struct.
-    /// This allows us to drop the List struct.
-    /// List will be deallocated if the reference count reaches zero.
-    /// Can cause undefined behavior if used without shsc::traits::RefCopy::refcopy.
+    /// Get the last item of the list
+    /// ### Returns
+    /// The last item, or None if the list is empty
     /// ### Example
     /// ```
-    /// use shsc::traits::RefCopy;
-    /// let mut list = shsc::List::from(vec![
-    ///     shsc::todata!(1),
-    ///     shsc::todata!(2),
-    ///     shsc::todata!(3),
-    /// ]);
-    /// {
-    ///     let list2 = list.refcopy();     // new reference copy
-    /// }                                   // drop reference copy
-    /// list.refdrop();                     // drop original reference copy
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// assert_eq!(list.peek_back().unwrap().as_i64(), Some(2));
+    /// assert!(shsc::List::new().peek_back().is_none());
     /// ```
 
-    fn refdrop(self) {
-        // drop trait is called here
+    pub fn peek_back(&self) -> Option<&crate::Data> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
     }
-}
 
-impl Clone for List {
-    /// Implement the Clone trait for the List struct.
-    /// This allows us to create a deep copy of the List struct.
-    /// Results in a new List struct with a reference count of 1.
+    /// Copy a range of the list into a new list.
+    /// Elements are deep copied, as with Clone.
+    /// ### Arguments
+    /// * `range` - Range of indices to copy
     /// ### Returns
-    /// A deep copy of the List struct
+    /// A new list holding the copied data
     /// ### Example
     /// ```
-    /// let list = shsc::List::from(vec![
-    ///     shsc::todata!(1),
-    ///     shsc::todata!(2),
-    ///     shsc::todata!(3),
-    /// ]);
-    /// let list1 = list.clone();
+    /// use shsc::traits::ToStr;
+    /// let list: shsc::List = (0..5).map(|i| shsc::todata!(i)).collect();
+    /// assert_eq!(list.slice(1..3).tostr(), "[1,2]");
+    /// assert_eq!(list.slice(3..).tostr(), "[3,4]");
+    /// assert_eq!(list.len(), 5);
     /// ```
 
-    fn clone(&self) -> Self {
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> List {
+        let (start, end) = self.range(range, "slice");
         let mut newlist = List::new();
-        for &item in self.as_vec().iter() {
-            newlist.append(item.clone());
-        }
+        newlist.extend(self.iter_range(start, end).cloned());
         newlist
     }
-}
 
-impl traits::RefC for List {
-    /// Implement the RefC trait for the List struct.
-    /// This allows us to increment the reference count of the List struct.
-    fn incrc(&mut self) {
-        unsafe { *self.refc += 1 };
-    }
+    /// Replace a range of the list with the items of an iterator.
+    /// The tail of the list is moved at most once, however many items are removed or added.
+    /// ### Arguments
+    /// * `range` - Range of indices to replace
+    /// * `replacement` - Data to insert in place of the range
+    /// ### Returns
+    /// A new list holding the removed data
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list: shsc::List = (0..5).map(|i| shsc::todata!(i)).collect();
+    /// let removed = list.splice(1..4, vec![shsc::todata!("a"), shsc::todata!("b")]);
+    /// assert_eq!(removed.tostr(), "[1,2,3]");
+    /// assert_eq!(list.tostr(), "[0,a,b,4]");
+    /// ```
 
-    /// Implement the RefC trait for the List struct.
-    /// This allows us to decrement the reference count of the List struct.
-    fn decrc(&mut self) {
+    pub fn splice<R, I>(&mut self, range: R, replacement: I) -> List
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = crate::Data>,
+    {
+        let (start, end) = self.range(range, "splice");
+        let items: Vec<crate::Data> = replacement.into_iter().collect();
+        let (count, removing) = (items.len(), end - start);
         unsafe {
-            *self.refc -= 1;
-            if (*self.refc) < 0 {
-                *self.refc = 0;
+            let tail = (*self.len) - end;
+            let mut removed = List::new();
+            if let Err(err) = removed.try_make_room(removing) {
+                err.abort();
+            }
+            if count > removing {
+                if let Err(err) = self.try_make_room(count - removing) {
+                    err.abort();
+                }
+            }
+            List::move_block(self, start, &removed, 0, removing);
+            *removed.len = removing;
+            // shift the tail straight to the end of the replacement, then fill the gap
+            List::move_block(self, end, self, start + count, tail);
+            for (i, item) in items.into_iter().enumerate() {
+                self.slot(start + i).write(item);
+            }
+            *self.len = start + count + tail;
+            if count < removing {
+                self.trim();
             }
+            removed
         }
     }
 
-    /// Implement the RefC trait for the List struct.
-    /// This allows us to get the reference count of the List struct.
-    fn getrc(&self) -> i64 {
-        unsafe { *self.refc }
-    }
-}
-
+    /// Remove a range of the list, yielding the removed data.
+    /// The range is removed even if the iterator is not fully consumed.
+    /// ### Arguments
+    /// * `range` - Range of indices to remove
+    /// ### Returns
+    /// An iterator moving the removed data out of the list
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list: shsc::List = (0..1000).map(|i| shsc::todata!(i)).collect();
+    /// let sum: i64 = list.drain(..998).filter_map(|d| d.as_i64()).sum();
+    /// assert_eq!(sum, (0..998).sum());
+    /// assert_eq!(list.tostr(), "[998,999]");
+    /// ```
+
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_> {
+        let (start, end) = self.range(range, "drain");
+        unsafe {
+            let tail = (*self.len) - end;
+            // the list only owns the items before the range until the drain is dropped
+            *self.len = start;
+            Drain {
+                list: self,
+                cursor: Cursor::new(start, end),
+                end,
+                tail,
+            }
+        }
+    }
+
+    /// Shorten the list, dropping the items past the new length.
+    /// Does nothing if the list is already short enough.
+    /// ### Arguments
+    /// * `len` - The new length of the list
+    /// ### Example
+    /// ```
+    /// let mut list: shsc::List = (0..5).map(|i| shsc::todata!(i)).collect();
+    /// list.truncate(2);
+    /// assert_eq!(list.len(), 2);
+    /// list.truncate(10);
+    /// assert_eq!(list.len(), 2);
+    /// ```
+
+    pub fn truncate(&mut self, len: usize) {
+        unsafe {
+            let oldlen = *self.len;
+            if len >= oldlen {
+                return;
+            }
+            // shorten first, so the dropped items never see themselves in the list
+            *self.len = len;
+            for i in len..oldlen {
+                ptr::drop_in_place(self.slot(i));
+            }
+            self.trim();
+        }
+    }
+
+    /// Remove every item from the list
+    /// ### Example
+    /// ```
+    /// let mut list: shsc::List = (0..5).map(|i| shsc::todata!(i)).collect();
+    /// list.clear();
+    /// assert_eq!(list.len(), 0);
+    /// ```
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Split the list in two at an index.
+    /// The items from the index onward are moved into a new list.
+    /// ### Arguments
+    /// * `at` - Index to split at
+    /// ### Returns
+    /// A new list holding the items from the index onward
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list: shsc::List = (0..5).map(|i| shsc::todata!(i)).collect();
+    /// let tail = list.split_off(3);
+    /// assert_eq!(list.tostr(), "[0,1,2]");
+    /// assert_eq!(tail.tostr(), "[3,4]");
+    /// ```
+
+    pub fn split_off(&mut self, at: usize) -> List {
+        unsafe {
+            if at > (*self.len) {
+                panic!("shsc::List: split_off: index out of bounds for {}", at);
+            }
+            let count = (*self.len) - at;
+            let mut newlist = List::new();
+            if let Err(err) = newlist.try_make_room(count) {
+                err.abort();
+            }
+            List::move_block(self, at, &newlist, 0, count);
+            *self.len = at;
+            *newlist.len = count;
+            self.trim();
+            newlist
+        }
+    }
+
+    /// Append deep copies of a slice of data to the list
+    /// ### Arguments
+    /// * `items` - Data to append
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::from(vec![shsc::todata!(1)]);
+    /// list.extend_from_slice(&[shsc::todata!(2), shsc::todata!(3)]);
+    /// assert_eq!(list.len(), 3);
+    /// ```
+
+    pub fn extend_from_slice(&mut self, items: &[crate::Data]) {
+        self.extend(items.iter().cloned());
+    }
+
+    /// Sort the list, keeping equal items in their original order.
+    /// Mixed types are ordered by the shsc::Data ordering, which ranks types before values.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list = shsc::List::from(vec![
+    ///     shsc::todata!("b"),
+    ///     shsc::todata!(3),
+    ///     shsc::todata!("a"),
+    ///     shsc::todata!(1),
+    /// ]);
+    /// list.sort();
+    /// assert_eq!(list.tostr(), "[1,3,a,b]");
+    /// ```
+
+    pub fn sort(&mut self) {
+        self.sort_by(|a, b| a.cmp(b));
+    }
+
+    /// Sort the list with a comparator, keeping equal items in their original order.
+    /// If compare panics, the list keeps all of its items in an unspecified order.
+    /// ### Arguments
+    /// * `compare` - Function ordering two items
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list: shsc::List = (0..5).map(|i| shsc::todata!(i)).collect();
+    /// list.sort_by(|a, b| b.cmp(a));
+    /// assert_eq!(list.tostr(), "[4,3,2,1,0]");
+    ///
+    /// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    ///     list.sort_by(|_, _| panic!("no order"));
+    /// }));
+    /// assert!(result.is_err());
+    /// let mut items: Vec<_> = list.iter().map(|d| d.as_i64().unwrap()).collect();
+    /// items.sort();
+    /// assert_eq!(items, [0, 1, 2, 3, 4]);
+    /// ```
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&crate::Data, &crate::Data) -> Ordering,
+    {
+        self.with_contiguous(|items| items.sort_by(&mut compare));
+    }
+
+    /// Sort the list by a key, keeping items with equal keys in their original order.
+    /// ### Arguments
+    /// * `key` - Function extracting the key of an item
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list = shsc::List::from(vec![
+    ///     shsc::todata!("ccc"),
+    ///     shsc::todata!("a"),
+    ///     shsc::todata!("bb"),
+    /// ]);
+    /// list.sort_by_key(|d| d.as_str().map(str::len));
+    /// assert_eq!(list.tostr(), "[a,bb,ccc]");
+    /// ```
+
+    pub fn sort_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: Ord,
+        F: FnMut(&crate::Data) -> K,
+    {
+        self.with_contiguous(|items| items.sort_by_key(&mut key));
+    }
+
+    /// Sort the list, in any order for equal items.
+    /// ### Example
+    /// ```
+    /// let mut list: shsc::List = (0..100).rev().map(|i| shsc::todata!(i)).collect();
+    /// list.sort_unstable();
+    /// assert!(list.iter().zip(0..).all(|(d, i)| d.as_i64() == Some(i)));
+    /// ```
+
+    pub fn sort_unstable(&mut self) {
+        self.sort_unstable_by(|a, b| a.cmp(b));
+    }
+
+    /// Sort the list with a comparator, in any order for equal items.
+    /// If compare panics, the list keeps all of its items in an unspecified order.
+    /// ### Arguments
+    /// * `compare` - Function ordering two items
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list: shsc::List = (0..5).map(|i| shsc::todata!(i)).collect();
+    /// list.sort_unstable_by(|a, b| b.cmp(a));
+    /// assert_eq!(list.tostr(), "[4,3,2,1,0]");
+    ///
+    /// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    ///     list.sort_unstable_by(|_, _| panic!("no order"));
+    /// }));
+    /// assert!(result.is_err());
+    /// assert_eq!(list.len(), 5);
+    /// ```
+
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&crate::Data, &crate::Data) -> Ordering,
+    {
+        self.with_contiguous(|items| items.sort_unstable_by(&mut compare));
+    }
+
+    /// Search a sorted list for an item.
+    /// ### Arguments
+    /// * `item` - Item to search for
+    /// ### Returns
+    /// Ok with the index of a matching item, or Err with the index where it could be inserted
+    /// ### Example
+    /// ```
+    /// let list: shsc::List = (0..10).map(|i| shsc::todata!(i * 2)).collect();
+    /// assert_eq!(list.binary_search(&shsc::todata!(6)), Ok(3));
+    /// assert_eq!(list.binary_search(&shsc::todata!(7)), Err(4));
+    /// ```
+
+    pub fn binary_search(&self, item: &crate::Data) -> Result<usize, usize> {
+        self.binary_search_by(|probe| probe.cmp(item))
+    }
+
+    /// Search a list sorted by a comparator.
+    /// ### Arguments
+    /// * `compare` - Function ordering an item against the target
+    /// ### Returns
+    /// Ok with the index of a matching item, or Err with the index where it could be inserted
+    /// ### Example
+    /// ```
+    /// let list: shsc::List = (0..10).rev().map(|i| shsc::todata!(i)).collect();
+    /// let target = shsc::todata!(7);
+    /// assert_eq!(list.binary_search_by(|probe| target.cmp(probe)), Ok(2));
+    /// ```
+
+    pub fn binary_search_by<F>(&self, mut compare: F) -> Result<usize, usize>
+    where
+        F: FnMut(&crate::Data) -> Ordering,
+    {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match compare(&self[mid]) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(low)
+    }
+
+    /// Reverse the order of the list in place
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list: shsc::List = (0..4).map(|i| shsc::todata!(i)).collect();
+    /// list.reverse();
+    /// assert_eq!(list.tostr(), "[3,2,1,0]");
+    /// ```
+
+    pub fn reverse(&mut self) {
+        let len = self.len();
+        for i in 0..len / 2 {
+            unsafe { ptr::swap(self.slot(i), self.slot(len - 1 - i)) };
+        }
+    }
+
+    /// Remove consecutive equal items, keeping the first of each run
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(1),
+    /// ]);
+    /// list.dedup();
+    /// assert_eq!(list.tostr(), "[1,2,1]");
+    /// ```
+
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Remove consecutive items that a predicate considers the same, keeping the first of each run.
+    /// ### Arguments
+    /// * `same` - Function called with an item and the last kept item
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let mut list: shsc::List = (0..10).map(|i| shsc::todata!(i)).collect();
+    /// list.dedup_by(|a, b| a.as_i64().unwrap() / 4 == b.as_i64().unwrap() / 4);
+    /// assert_eq!(list.tostr(), "[0,4,8]");
+    /// ```
+
+    pub fn dedup_by<F>(&mut self, mut same: F)
+    where
+        F: FnMut(&crate::Data, &crate::Data) -> bool,
+    {
+        unsafe {
+            let len = *self.len;
+            if len < 2 {
+                return;
+            }
+            // only the kept items belong to the list while it is compacted,
+            // so a panicking predicate leaks the unvisited items instead of dropping them twice
+            let mut kept = 1;
+            *self.len = kept;
+            for i in 1..len {
+                if same(&*self.slot(i), &*self.slot(kept - 1)) {
+                    ptr::drop_in_place(self.slot(i));
+                } else {
+                    if i != kept {
+                        self.slot(kept).write(self.slot(i).read());
+                    }
+                    kept += 1;
+                    *self.len = kept;
+                }
+            }
+            self.trim();
+        }
+    }
+
+    /// Check whether the list holds an item equal to the given one
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!("a")]);
+    /// assert!(list.contains(&shsc::todata!("a")));
+    /// assert!(!list.contains(&shsc::todata!(2)));
+    /// ```
+
+    pub fn contains(&self, item: &crate::Data) -> bool {
+        self.iter().any(|probe| probe == item)
+    }
+
+    /// Find the index of the first item matching a predicate
+    /// ### Arguments
+    /// * `predicate` - Function returning true for the wanted item
+    /// ### Returns
+    /// The index of the first matching item, or None if no item matches
+    /// ### Example
+    /// ```
+    /// let list: shsc::List = (0..10).map(|i| shsc::todata!(i)).collect();
+    /// assert_eq!(list.position(|d| d.as_i64() > Some(6)), Some(7));
+    /// assert_eq!(list.position(|d| d.is_null()), None);
+    /// ```
+
+    pub fn position<F>(&self, predicate: F) -> Option<usize>
+    where
+        F: FnMut(&crate::Data) -> bool,
+    {
+        self.iter().position(predicate)
+    }
+
+    /// Build a new list from the result of a function on each item
+    /// ### Arguments
+    /// * `f` - Function mapping an item to new data
+    /// ### Returns
+    /// A new list holding the mapped data
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list: shsc::List = (1..=3).map(|i| shsc::todata!(i)).collect();
+    /// let doubled = list.map(|d| shsc::todata!(d.as_i64().unwrap() * 2));
+    /// assert_eq!(doubled.tostr(), "[2,4,6]");
+    /// ```
+
+    pub fn map<F>(&self, f: F) -> List
+    where
+        F: FnMut(&crate::Data) -> crate::Data,
+    {
+        self.iter().map(f).collect()
+    }
+
+    /// Build a new list from deep copies of the items matching a predicate
+    /// ### Arguments
+    /// * `predicate` - Function returning true for the items to keep
+    /// ### Returns
+    /// A new list holding the matching data
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list: shsc::List = (1..=6).map(|i| shsc::todata!(i)).collect();
+    /// let even = list.filter(|d| d.as_i64().unwrap() % 2 == 0);
+    /// assert_eq!(even.tostr(), "[2,4,6]");
+    /// ```
+
+    pub fn filter<F>(&self, mut predicate: F) -> List
+    where
+        F: FnMut(&crate::Data) -> bool,
+    {
+        self.iter()
+            .filter(|item| predicate(item))
+            .cloned()
+            .collect()
+    }
+
+    /// Combine the items into one value, from first to last
+    /// ### Arguments
+    /// * `init` - The starting value
+    /// * `f` - Function combining the value so far with an item
+    /// ### Returns
+    /// The final value
+    /// ### Example
+    /// ```
+    /// let list: shsc::List = (1..=4).map(|i| shsc::todata!(i)).collect();
+    /// let product = list.fold(1, |acc, d| acc * d.as_i64().unwrap());
+    /// assert_eq!(product, 24);
+    /// ```
+
+    pub fn fold<T, F>(&self, init: T, f: F) -> T
+    where
+        F: FnMut(T, &crate::Data) -> T,
+    {
+        self.iter().fold(init, f)
+    }
+
+    /// Find the first item matching a predicate
+    /// ### Arguments
+    /// * `predicate` - Function returning true for the wanted item
+    /// ### Returns
+    /// A reference to the first matching item, or None if no item matches
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!("a")]);
+    /// assert_eq!(list.find(|d| d.as_str().is_some()).unwrap().as_str(), Some("a"));
+    /// ```
+
+    pub fn find<F>(&self, mut predicate: F) -> Option<&crate::Data>
+    where
+        F: FnMut(&crate::Data) -> bool,
+    {
+        self.iter().find(|item| predicate(item))
+    }
+
+    /// Check whether any item matches a predicate
+    /// ### Example
+    /// ```
+    /// let list: shsc::List = (1..=3).map(|i| shsc::todata!(i)).collect();
+    /// assert!(list.any(|d| d.as_i64() == Some(2)));
+    /// assert!(!shsc::List::new().any(|_| true));
+    /// ```
+
+    pub fn any<F>(&self, predicate: F) -> bool
+    where
+        F: FnMut(&crate::Data) -> bool,
+    {
+        self.iter().any(predicate)
+    }
+
+    /// Check whether every item matches a predicate
+    /// ### Example
+    /// ```
+    /// let list: shsc::List = (1..=3).map(|i| shsc::todata!(i)).collect();
+    /// assert!(list.all(|d| d.as_i64() > Some(0)));
+    /// assert!(shsc::List::new().all(|_| false));
+    /// ```
+
+    pub fn all<F>(&self, predicate: F) -> bool
+    where
+        F: FnMut(&crate::Data) -> bool,
+    {
+        self.iter().all(predicate)
+    }
+
+    /// Build a new list from the items produced by a function on each item
+    /// ### Arguments
+    /// * `f` - Function mapping an item to any number of new items, such as a shsc::List
+    /// ### Returns
+    /// A new list holding all produced items in order
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list: shsc::List = (1..=3).map(|i| shsc::todata!(i)).collect();
+    /// let repeated = list.flat_map(|d| vec![d.clone(); d.as_i64().unwrap() as usize]);
+    /// assert_eq!(repeated.tostr(), "[1,2,2,3,3,3]");
+    /// ```
+
+    pub fn flat_map<I, F>(&self, f: F) -> List
+    where
+        I: IntoIterator<Item = crate::Data>,
+        F: FnMut(&crate::Data) -> I,
+    {
+        self.iter().flat_map(f).collect()
+    }
+
+    /// Pair up the items of two lists, stopping at the end of the shorter one
+    /// ### Arguments
+    /// * `other` - A shsc::List struct
+    /// ### Returns
+    /// A new list of two item lists, holding deep copies of the paired items
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let keys = shsc::List::from(vec![shsc::todata!("a"), shsc::todata!("b")]);
+    /// let values: shsc::List = (1..=3).map(|i| shsc::todata!(i)).collect();
+    /// assert_eq!(keys.zip(&values).tostr(), "[[a,1],[b,2]]");
+    /// ```
+
+    pub fn zip(&self, other: &List) -> List {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| crate::todata!(List::from(vec![a.clone(), b.clone()])))
+            .collect()
+    }
+
+    /// Pair each item with its index
+    /// ### Returns
+    /// A new list of two item lists, holding the index as INT64 and a deep copy of the item
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list = shsc::List::from(vec![shsc::todata!("a"), shsc::todata!("b")]);
+    /// assert_eq!(list.enumerate().tostr(), "[[0,a],[1,b]]");
+    /// ```
+
+    pub fn enumerate(&self) -> List {
+        self.iter()
+            .enumerate()
+            .map(|(i, item)| {
+                crate::todata!(List::from(vec![crate::todata!(i as i64), item.clone()]))
+            })
+            .collect()
+    }
+
+    /// Split the list into consecutive chunks
+    /// ### Arguments
+    /// * `size` - Number of items in each chunk, the last chunk may be shorter
+    /// ### Returns
+    /// A new list of lists, holding deep copies of the items
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list: shsc::List = (1..=5).map(|i| shsc::todata!(i)).collect();
+    /// assert_eq!(list.chunks(2).tostr(), "[[1,2],[3,4],[5]]");
+    /// ```
+
+    pub fn chunks(&self, size: usize) -> List {
+        if size == 0 {
+            panic!("shsc::List: chunks: chunk size must be non-zero");
+        }
+        (0..self.len())
+            .step_by(size)
+            .map(|start| crate::todata!(self.slice(start..self.len().min(start + size))))
+            .collect()
+    }
+
+    /// Get every run of consecutive items of a given length
+    /// ### Arguments
+    /// * `size` - Number of items in each window
+    /// ### Returns
+    /// A new list of lists, holding deep copies of the items,
+    /// empty if the list is shorter than the window
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list: shsc::List = (1..=4).map(|i| shsc::todata!(i)).collect();
+    /// assert_eq!(list.windows(3).tostr(), "[[1,2,3],[2,3,4]]");
+    /// ```
+
+    pub fn windows(&self, size: usize) -> List {
+        if size == 0 {
+            panic!("shsc::List: windows: window size must be non-zero");
+        }
+        if size > self.len() {
+            return List::new();
+        }
+        (0..=self.len() - size)
+            .map(|start| crate::todata!(self.slice(start..start + size)))
+            .collect()
+    }
+
+    /// Group the items by a key
+    /// ### Arguments
+    /// * `key` - Function returning the key of an item
+    /// ### Returns
+    /// A new map from each key to a list of deep copies of its items, in list order
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list: shsc::List = (1..=5).map(|i| shsc::todata!(i)).collect();
+    /// let groups = list.group_by(|d| if d.as_i64().unwrap() % 2 == 0 { "even" } else { "odd" });
+    /// assert_eq!(groups["odd"].tostr(), "[1,3,5]");
+    /// assert_eq!(groups["even"].tostr(), "[2,4]");
+    /// ```
+
+    pub fn group_by<K, F>(&self, mut key: F) -> crate::Map
+    where
+        K: crate::symbol::Key,
+        F: FnMut(&crate::Data) -> K,
+    {
+        let mut groups = crate::Map::new();
+        for item in self.iter() {
+            let key = key(item).intern();
+            match groups.get_mut(&key).and_then(crate::Data::as_list_mut) {
+                Some(group) => group.append(item.clone()),
+                None => groups.insert(key, crate::todata!(List::from(vec![item.clone()]))),
+            }
+        }
+        groups
+    }
+
+    /// Concatenate two lists into a new list.
+    /// Elements are deep copied, as with Clone.
+    /// ### Arguments
+    /// * `other` - A shsc::List struct
+    /// ### Returns
+    /// A new shsc::List struct
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// let list2 = shsc::List::from(vec![shsc::todata!(3)]);
+    /// assert_eq!(list.concat(&list2).tostr(), "[1,2,3]");
+    /// ```
+
+    pub fn concat(&self, other: &List) -> List {
+        let mut newlist = List::new();
+        for item in self.iter().chain(other.iter()) {
+            newlist.append(item.clone());
+        }
+        newlist
+    }
+
+    /// Get the list for mutation without affecting its reference copies.
+    /// The list is copied only if other reference copies exist, after which
+    /// this handle refers to the copy. The copy holds reference copies of
+    /// nested containers, which stay shared until they are made mutable too.
+    /// ### Returns
+    /// The list, no longer shared
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// let inner = shsc::List::from(vec![shsc::todata!(0)]);
+    /// let mut list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(inner)]);
+    /// let mut copy = list.refcopy();
+    /// copy.make_mut().append(shsc::todata!(2));
+    /// assert_eq!(list.len(), 2);
+    /// assert_eq!(copy.len(), 3);
+    /// // the nested list is shared until it is made mutable itself
+    /// copy[1].as_list_mut().unwrap().append(shsc::todata!(1));
+    /// assert_eq!(list[1].as_list().unwrap().len(), 2);
+    /// copy[1].as_list_mut().unwrap().make_mut().append(shsc::todata!(2));
+    /// assert_eq!(list[1].as_list().unwrap().len(), 2);
+    /// // no other reference copies, so nothing is copied
+    /// copy.make_mut().append(shsc::todata!(3));
+    /// assert_eq!(copy.len(), 4);
+    /// ```
+
+    pub fn make_mut(&mut self) -> &mut List {
+        if self.getrc() > 1 {
+            let mut copy = List::new();
+            copy.grow_for(self.len());
+            for item in self.iter_mut() {
+                copy.append(item.refcopy());
+            }
+            *self = copy;
+        }
+        self
+    }
+
+    /// Create a weak handle to the list
+    /// ### Returns
+    /// A weak handle that does not keep the list alive
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1)]);
+    /// let weak = list.downgrade();
+    /// assert_eq!(weak.upgrade().unwrap().len(), 1);
+    /// drop(list);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+
+    pub fn downgrade(&self) -> Weak {
+        unsafe { *self.weak += 1 };
+        Weak {
+            list: self.list,
+            len: self.len,
+            rows: self.rows,
+            cols: self.cols,
+            reserved: self.reserved,
+            first: self.first,
+            head: self.head,
+            heap: self.heap,
+            refc: self.refc,
+            weak: self.weak,
+        }
+    }
+
+    /// The allocator the list takes its memory from.
+    pub(crate) fn allocator(&self) -> &dyn alloc::Allocator {
+        unsafe { alloc::resolve(&*self.heap) }
+    }
+
+    /// Address identifying the list, shared by all its reference copies.
+    pub(crate) fn addr(&self) -> usize {
+        self.refc as usize
+    }
+
+    /// A handle to the list that does not own a reference.
+    /// Must not outlive the list.
+    pub(crate) unsafe fn shadow(&self) -> ManuallyDrop<List> {
+        ManuallyDrop::new(ptr::read(self))
+    }
+
+    /// Drop every item, leaving the list empty.
+    /// Items are dropped after the list is emptied, so they never see it half cleared.
+    pub(crate) fn release(&mut self) {
+        let mut items = Vec::with_capacity(self.len());
+        unsafe {
+            for i in 0..(*self.len) {
+                items.push(self.slot(i).read());
+            }
+            *self.len = 0;
+        }
+        drop(items);
+    }
+
+    /// Call f with the items moved into one contiguous buffer, then move them back.
+    /// The buffer comes from the heap of the list. The items are moved back
+    /// even if f panics, in whatever order f left them.
+    fn with_contiguous(&mut self, f: impl FnOnce(&mut [crate::Data])) {
+        unsafe {
+            let len = *self.len;
+            if len < 2 {
+                return;
+            }
+            let heap = alloc::resolve(&*self.heap);
+            let buffer = match alloc::try_allocate_in::<crate::Data>(heap, len) {
+                Ok(buffer) => buffer,
+                Err(err) => err.abort(),
+            };
+            let layout = self.layout();
+            // the items belong to the buffer until the guard moves them back
+            *self.len = 0;
+            let mut start = 0;
+            while start < len {
+                let run = layout.run_after(start).min(len - start);
+                ptr::copy_nonoverlapping(layout.slot(start), buffer.add(start), run);
+                start += run;
+            }
+            let guard = Contiguous {
+                list: self,
+                layout,
+                buffer,
+                len,
+            };
+            f(std::slice::from_raw_parts_mut(guard.buffer, len));
+        }
+    }
+
+    /// Resolve a range against the length of the list.
+    fn range<R: RangeBounds<usize>>(&self, range: R, op: &str) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        if start > end || end > self.len() {
+            panic!(
+                "shsc::List: {}: range out of bounds for {}..{}",
+                op, start, end
+            );
+        }
+        (start, end)
+    }
+
+    fn iter_range(&self, start: usize, end: usize) -> Iter<'_> {
+        Iter {
+            list: self,
+            cursor: Cursor::new(start, end),
+        }
+    }
+
+    /// Move count items from src[from..] to dst[to..], a run of contiguous slots at a time.
+    /// The ranges may overlap if src and dst are the same list.
+    /// Caller must ensure both ranges lie in allocated rows, and must treat
+    /// the source slots as uninitialized afterwards.
+    unsafe fn move_block(src: &List, from: usize, dst: &List, to: usize, count: usize) {
+        // moving right within one list must start from the end
+        let backward = src.list == dst.list && to > from;
+        let (src, dst) = (src.layout(), dst.layout());
+        let mut moved = 0;
+        while moved < count {
+            let remaining = count - moved;
+            let (srcpos, dstpos, run) = if backward {
+                let (srcend, dstend) = (from + remaining, to + remaining);
+                let run = remaining
+                    .min(src.run_before(srcend))
+                    .min(dst.run_before(dstend));
+                (srcend - run, dstend - run, run)
+            } else {
+                let (srcpos, dstpos) = (from + moved, to + moved);
+                let run = remaining
+                    .min(src.run_after(srcpos))
+                    .min(dst.run_after(dstpos));
+                (srcpos, dstpos, run)
+            };
+            ptr::copy(src.slot(srcpos), dst.slot(dstpos), run);
+            moved += run;
+        }
+    }
+
+    /// Where the items currently sit in the rows of the list.
+    fn layout(&self) -> Layout {
+        unsafe {
+            Layout {
+                table: *self.list,
+                rows: *self.rows,
+                cols: *self.cols,
+                first: *self.first,
+                head: *self.head,
+            }
+        }
+    }
+
+    /// Pointer to the slot holding the item at index.
+    /// Caller must ensure the row holding index has been allocated.
+    unsafe fn slot(&self, index: usize) -> *mut crate::Data {
+        self.layout().slot(index)
+    }
+
+    /// Add rows until there is room for additional more items, aborting on failure.
+    /// Unlike reserve, the room is not kept through removals.
+    fn grow_for(&mut self, additional: usize) {
+        if let Err(err) = self.try_make_room(additional) {
+            err.abort();
+        }
+    }
+
+    /// Add rows until there is room for additional more items.
+    pub(crate) fn try_make_room(&mut self, additional: usize) -> Result<(), AllocError> {
+        unsafe {
+            let rows = ((*self.head) + (*self.len) + additional).div_ceil(*self.cols);
+            if rows > (*self.rows) {
+                self.try_grow(rows)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Make room for one more item at the front of the list.
+    unsafe fn grow_front(&mut self) {
+        if let Err(err) = self.try_reserve_front() {
+            err.abort();
+        }
+    }
+
+    /// Add a row if the first row has no free slot before the first item.
+    unsafe fn try_reserve_front(&mut self) -> Result<(), AllocError> {
+        // the row before the first row is free if a whole row is unused
+        if (*self.head) == 0 && (*self.rows) * (*self.cols) < (*self.len) + (*self.cols) {
+            self.try_grow((*self.rows) + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Move the start of the list one slot back, into a free slot or row.
+    /// Caller must have reserved room at the front.
+    unsafe fn shift_head_back(&mut self) {
+        if (*self.head) == 0 {
+            *self.first = ((*self.first) + (*self.rows) - 1) % (*self.rows);
+            *self.head = (*self.cols) - 1;
+        } else {
+            *self.head -= 1;
+        }
+    }
+
+    /// Move the start of the list one slot forward, after the first slot has been vacated.
+    unsafe fn shift_head_forward(&mut self) {
+        *self.head += 1;
+        if (*self.head) == (*self.cols) {
+            *self.head = 0;
+            *self.first = ((*self.first) + 1) % (*self.rows);
+        }
+    }
+
+    /// Add rows if the list has reached capacity.
+    /// The row table is shared by all reference copies, so it is updated in place.
+    unsafe fn grow(&mut self) {
+        if let Err(err) = self.try_make_room(1) {
+            err.abort();
+        }
+    }
+
+    /// Number of rows holding items.
+    unsafe fn rows_in_use(&self) -> usize {
+        ((*self.head) + (*self.len)).div_ceil(*self.cols)
+    }
+
+    /// Free unused rows once fewer than a quarter of the rows are in use,
+    /// keeping twice the rows in use or reserved.
+    /// Called after removals, so a list that shrinks returns its memory without
+    /// giving back rows it is about to grow into again.
+    fn trim(&mut self) {
+        unsafe {
+            if (*self.len) == 0 {
+                *self.head = 0;
+            }
+            let reserved = ((*self.head) + (*self.reserved)).div_ceil(*self.cols);
+            let used = self.rows_in_use().max(reserved).max(1);
+            if (*self.rows) > 4 * used {
+                self.release_rows(2 * used);
+            }
+        }
+    }
+
+    /// Keep the rows in use and the spare rows after them up to keep rows, freeing the rest.
+    /// Caller must ensure keep covers the rows in use.
+    unsafe fn release_rows(&mut self, keep: usize) {
+        let rows = *self.rows;
+        if keep >= rows {
+            return;
+        }
+        let heap = alloc::resolve(&*self.heap);
+        // allocate the smaller table first, so a failure leaves the list as it was
+        let table = if keep == 0 {
+            ptr::null_mut()
+        } else {
+            match alloc::try_allocate_in::<*mut crate::Data>(heap, keep) {
+                Ok(table) => table,
+                Err(_) => return,
+            }
+        };
+        let first = *self.first;
+        for i in 0..rows {
+            let row = *(*self.list).add((first + i) % rows);
+            if i < keep {
+                table.add(i).write(row);
+            } else {
+                alloc::deallocate_in::<crate::Data>(heap, row, *self.cols);
+            }
+        }
+        alloc::deallocate_in::<*mut crate::Data>(heap, *self.list, rows);
+        *self.list = table;
+        *self.rows = keep;
+        *self.first = 0;
+    }
+
+    /// Grow the list to at least rows rows, doubling the number of rows if
+    /// that is more, so adding items at either end takes amortized constant time.
+    /// If the heap refuses the doubled rows, only the rows asked for are added.
+    unsafe fn try_grow(&mut self, rows: usize) -> Result<(), AllocError> {
+        let doubled = rows.max(2 * (*self.rows));
+        if doubled > rows && self.try_add_rows(doubled - (*self.rows)).is_ok() {
+            return Ok(());
+        }
+        self.try_add_rows(rows - (*self.rows))
+    }
+
+    /// Add count rows after the last row in use.
+    /// The row table is rotated so the first row is at the start, leaving
+    /// any free rows and the new rows after the rows in use.
+    /// Nothing is added if the heap refuses any of the memory.
+    unsafe fn try_add_rows(&mut self, count: usize) -> Result<(), AllocError> {
+        let heap = alloc::resolve(&*self.heap);
+        // allocate the rows first, so a failure leaves the row table consistent
+        let mut newrows = Vec::with_capacity(count);
+        for _ in 0..count {
+            match alloc::try_allocate_in::<crate::Data>(heap, *self.cols) {
+                Ok(row) => newrows.push(row),
+                Err(err) => {
+                    for row in newrows {
+                        alloc::deallocate_in::<crate::Data>(heap, row, *self.cols);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        match alloc::try_reallocate_in::<*mut crate::Data>(
+            heap,
+            *self.list,
+            *self.rows,
+            (*self.rows) + count,
+        ) {
+            Ok(table) => *self.list = table,
+            Err(err) => {
+                for row in newrows {
+                    alloc::deallocate_in::<crate::Data>(heap, row, *self.cols);
+                }
+                return Err(err);
+            }
+        }
+        if (*self.first) > 0 {
+            std::slice::from_raw_parts_mut(*self.list, *self.rows).rotate_left(*self.first);
+            *self.first = 0;
+        }
+        for (i, row) in newrows.into_iter().enumerate() {
+            (*self.list).add((*self.rows) + i).write(row);
+        }
+        *self.rows += count;
+        Ok(())
+    }
+}
+
+impl ops::Index<usize> for List {
+    type Output = crate::Data;
+
+    /// Implement the Index trait for the List struct.
+    /// This allows us to index the List struct.
+    /// ### Arguments
+    /// * `index` - Index of data
+    /// ### Returns
+    /// Data at index
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(3),
+    /// ]);
+    /// let data = &list[1];
+    /// ```
+
+    fn index(&self, index: usize) -> &crate::Data {
+        self.get(index)
+            .expect(&format!("shsc::List: index: undefined data at {}", index))
+    }
+}
+
+impl ops::IndexMut<usize> for List {
+    /// Implement the IndexMut trait for the List struct.
+    /// This allows us to index the List struct and modify the data at the index.
+    /// ### Arguments
+    /// * `index` - Index of data
+    /// ### Returns
+    /// Mutable data at index
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(3),
+    /// ]);
+    /// list[1] = shsc::todata!(4);
+    /// ```
+
+    fn index_mut(&mut self, index: usize) -> &mut crate::Data {
+        self.get_mut(index).expect(&format!(
+            "shsc::List: index_mut: undefined data at {}",
+            index
+        ))
+    }
+}
+
+impl traits::ToStr for List {
+    /// Get list as string
+    /// ### Returns
+    /// List as string
+    /// ### Example
+    /// ```
+    /// use crate::shsc::traits::ToStr;
+    /// let list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(3),
+    /// ]);
+    /// let s = list.tostr();
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        let mut s = String::new();
+        s.push('[');
+        for (i, item) in self.iter().enumerate() {
+            s.push_str(&item.tostr());
+            if i < self.len() - 1 {
+                s.push(',');
+            }
+        }
+        s.push(']');
+        s
+    }
+}
+
+impl traits::RefCopy for List {
+    /// Implement the RefCopy trait for the List struct.
+    /// This allows us to create a reference copy of the List struct.
+    /// ### Returns
+    /// A reference copy of the list
+    /// ### Example
+    /// ```
+    /// use crate::shsc::traits::RefCopy;
+    /// let mut list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(3),
+    /// ]);
+    /// let list1 = list.refcopy();
+    /// ```
+
+    fn refcopy(&mut self) -> Self {
+        self.incrc();
+        List {
+            list: self.list,
+            len: self.len,
+            rows: self.rows,
+            cols: self.cols,
+            reserved: self.reserved,
+            first: self.first,
+            head: self.head,
+            heap: self.heap,
+            refc: self.refc,
+            weak: self.weak,
+        }
+    }
+
+    /// Implement the RefDrop trait for the List struct.
+    /// This allows us to drop the List struct.
+    /// List will be deallocated if the reference count reaches zero.
+    /// Can cause undefined behavior if used without shsc::traits::RefCopy::refcopy.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// let mut list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(3),
+    /// ]);
+    /// {
+    ///     let list2 = list.refcopy();     // new reference copy
+    /// }                                   // drop reference copy
+    /// list.refdrop();                     // drop original reference copy
+    /// ```
+
+    fn refdrop(self) {
+        // drop trait is called here
+    }
+}
+
+impl Clone for List {
+    /// Implement the Clone trait for the List struct.
+    /// This allows us to create a deep copy of the List struct.
+    /// Results in a new List struct with a reference count of 1.
+    /// ### Returns
+    /// A deep copy of the List struct
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![
+    ///     shsc::todata!(1),
+    ///     shsc::todata!(2),
+    ///     shsc::todata!(3),
+    /// ]);
+    /// let list1 = list.clone();
+    /// ```
+
+    fn clone(&self) -> Self {
+        let mut newlist = List::new();
+        for item in self.iter() {
+            newlist.append(item.clone());
+        }
+        newlist
+    }
+}
+
+impl PartialEq for List {
+    /// Implement the PartialEq trait for the List struct.
+    /// Lists are equal if they have equal elements in the same order.
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// let list1 = list.clone();
+    /// assert!(list == list1);
+    /// ```
+
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for List {}
+
+impl PartialOrd for List {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for List {
+    /// Implement the Ord trait for the List struct.
+    /// Lists compare lexicographically by element, using the shsc::Data ordering.
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// let list1 = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(3)]);
+    /// let list2 = shsc::List::from(vec![shsc::todata!(1)]);
+    /// assert!(list < list1);
+    /// assert!(list2 < list);
+    /// ```
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reference copies of one list are trivially equal
+        if self.refc == other.refc {
+            return Ordering::Equal;
+        }
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl Hash for List {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
+impl traits::RefC for List {
+    /// Implement the RefC trait for the List struct.
+    /// This allows us to increment the reference count of the List struct.
+    fn incrc(&mut self) {
+        unsafe { *self.refc += 1 };
+    }
+
+    /// Implement the RefC trait for the List struct.
+    /// This allows us to decrement the reference count of the List struct.
+    fn decrc(&mut self) {
+        unsafe {
+            *self.refc -= 1;
+            if (*self.refc) < 0 {
+                *self.refc = 0;
+            }
+        }
+    }
+
+    /// Implement the RefC trait for the List struct.
+    /// This allows us to get the reference count of the List struct.
+    fn getrc(&self) -> i64 {
+        unsafe { *self.refc }
+    }
+}
+
 impl Drop for List {
     /// Implement the Drop trait for the List struct.
     /// This allows us to deallocate the List struct when the reference count reaches zero.
@@ -525,19 +1978,483 @@ impl Drop for List {
             if self.getrc() > 0 {
                 return;
             }
+            gc::untrack(self.addr());
+            debug::forget(self.addr());
+            // the contents may hold weak handles to this list,
+            // so keep the counters alive until they are dropped
+            *self.weak += 1;
+            // only the first len slots hold initialized data
+            for i in 0..(*self.len) {
+                ptr::drop_in_place(self.slot(i));
+            }
+            let heap = alloc::resolve(&*self.heap);
             for i in 0..(*self.rows) {
-                let row = *self.list.add(i);
-                for j in 0..(*self.cols) {
-                    // drop data in this scope
-                    row.add(j).read().refdrop();
-                }
-                alloc::deallocate::<crate::Data>(row, *self.cols);
+                alloc::deallocate_in::<crate::Data>(heap, *(*self.list).add(i), *self.cols);
+            }
+            if (*self.rows) > 0 {
+                alloc::deallocate_in::<*mut crate::Data>(heap, *self.list, *self.rows);
             }
-            alloc::deallocate::<*mut crate::Data>(self.list, *self.rows);
+            drop(Box::from_raw(self.list));
             drop(Box::from_raw(self.len));
             drop(Box::from_raw(self.rows));
             drop(Box::from_raw(self.cols));
-            drop(Box::from_raw(self.refc));
+            drop(Box::from_raw(self.reserved));
+            drop(Box::from_raw(self.first));
+            drop(Box::from_raw(self.head));
+            drop(Box::from_raw(self.heap));
+            *self.weak -= 1;
+            if (*self.weak) == 0 {
+                drop(Box::from_raw(self.refc));
+                drop(Box::from_raw(self.weak));
+            }
+        }
+    }
+}
+
+impl Weak {
+    /// Get a strong reference to the list if it is still alive
+    /// ### Returns
+    /// A reference copy of the list, or None if the list has been deallocated
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::new();
+    /// let weak = list.downgrade();
+    /// let mut strong = weak.upgrade().unwrap();
+    /// drop(list);
+    /// strong.append(shsc::todata!(1));
+    /// assert_eq!(weak.upgrade().unwrap().len(), 1);
+    /// drop(strong);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+
+    pub fn upgrade(&self) -> Option<List> {
+        unsafe {
+            if (*self.refc) <= 0 {
+                return None;
+            }
+            *self.refc += 1;
+        }
+        Some(List {
+            list: self.list,
+            len: self.len,
+            rows: self.rows,
+            cols: self.cols,
+            reserved: self.reserved,
+            first: self.first,
+            head: self.head,
+            heap: self.heap,
+            refc: self.refc,
+            weak: self.weak,
+        })
+    }
+
+    /// Get the number of strong references to the list
+    /// ### Returns
+    /// The strong reference count, zero once the list has been deallocated
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::new();
+    /// let weak = list.downgrade();
+    /// assert_eq!(weak.strong_count(), 1);
+    /// drop(list);
+    /// assert_eq!(weak.strong_count(), 0);
+    /// ```
+
+    pub fn strong_count(&self) -> i64 {
+        unsafe { *self.refc }
+    }
+
+    /// Check whether two weak handles point to the same list
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::new();
+    /// assert!(list.downgrade().ptr_eq(&list.downgrade()));
+    /// assert!(!list.downgrade().ptr_eq(&shsc::List::new().downgrade()));
+    /// ```
+
+    pub fn ptr_eq(&self, other: &Weak) -> bool {
+        self.refc == other.refc
+    }
+
+    pub(crate) fn addr(&self) -> usize {
+        self.refc as usize
+    }
+}
+
+impl Clone for Weak {
+    /// Implement the Clone trait for the Weak struct.
+    /// Results in another weak handle to the same list.
+    fn clone(&self) -> Self {
+        unsafe { *self.weak += 1 };
+        Weak {
+            list: self.list,
+            len: self.len,
+            rows: self.rows,
+            cols: self.cols,
+            reserved: self.reserved,
+            first: self.first,
+            head: self.head,
+            heap: self.heap,
+            refc: self.refc,
+            weak: self.weak,
+        }
+    }
+}
+
+impl Drop for Weak {
+    /// Implement the Drop trait for the Weak struct.
+    /// Frees the shared counters once no strong or weak references remain.
+    fn drop(&mut self) {
+        unsafe {
+            *self.weak -= 1;
+            if (*self.weak) == 0 && (*self.refc) <= 0 {
+                drop(Box::from_raw(self.refc));
+                drop(Box::from_raw(self.weak));
+            }
+        }
+    }
+}
+
+/// Position of an iterator within the rows of a list.
+/// Items are visited row by row, so only moving to the next row needs a division.
+struct Cursor {
+    row: *mut crate::Data,
+    col: usize,
+    front: usize,
+    back: usize,
+}
+
+impl Cursor {
+    fn new(front: usize, back: usize) -> Self {
+        Cursor {
+            row: ptr::null_mut(),
+            col: 0,
+            front,
+            back,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
+
+    /// Caller must ensure the layout holds at least back items.
+    /// Rows never move once allocated, so the current row stays valid
+    /// even if the table itself is reallocated.
+    unsafe fn next(&mut self, layout: Layout) -> Option<*mut crate::Data> {
+        if self.front >= self.back {
+            return None;
+        }
+        if self.row.is_null() || self.col == layout.cols {
+            let slot = layout.slot(self.front);
+            self.col = layout.cols - layout.run_after(self.front);
+            self.row = slot.sub(self.col);
+        }
+        let item = self.row.add(self.col);
+        self.col += 1;
+        self.front += 1;
+        Some(item)
+    }
+
+    unsafe fn next_back(&mut self, layout: Layout) -> Option<*mut crate::Data> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(layout.slot(self.back))
+    }
+}
+
+/// A snapshot of the row table of a list.
+/// Item index maps to slot head + index of the rows in use, which start at
+/// row first and wrap around the table, so items can be added at either end.
+#[derive(Clone, Copy)]
+struct Layout {
+    table: *mut *mut crate::Data,
+    rows: usize,
+    cols: usize,
+    first: usize,
+    head: usize,
+}
+
+impl Layout {
+    /// Caller must ensure the row holding index has been allocated.
+    unsafe fn slot(&self, index: usize) -> *mut crate::Data {
+        let pos = self.head + index;
+        let row = (self.first + pos / self.cols) % self.rows;
+        (*self.table.add(row)).add(pos % self.cols)
+    }
+
+    /// Number of contiguous slots from index to the end of its row.
+    fn run_after(&self, index: usize) -> usize {
+        self.cols - (self.head + index) % self.cols
+    }
+
+    /// Number of contiguous slots before end back to the start of their row.
+    fn run_before(&self, end: usize) -> usize {
+        (self.head + end - 1) % self.cols + 1
+    }
+}
+
+/// The items of a list moved out into a contiguous buffer.
+/// Dropping it moves them back and frees the buffer, so a panic while they
+/// are out neither empties the list nor leaks them.
+struct Contiguous<'a> {
+    list: &'a List,
+    layout: Layout,
+    buffer: *mut crate::Data,
+    len: usize,
+}
+
+impl Drop for Contiguous<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut start = 0;
+            while start < self.len {
+                let run = self.layout.run_after(start).min(self.len - start);
+                ptr::copy_nonoverlapping(self.buffer.add(start), self.layout.slot(start), run);
+                start += run;
+            }
+            *self.list.len = self.len;
+            let heap = alloc::resolve(&*self.list.heap);
+            alloc::deallocate_in::<crate::Data>(heap, self.buffer, self.len);
+        }
+    }
+}
+
+/// An iterator over references to the data in a shsc::List.
+pub struct Iter<'a> {
+    list: &'a List,
+    cursor: Cursor,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a crate::Data;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { self.cursor.next(self.list.layout()).map(|item| &*item) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursor.len(), Some(self.cursor.len()))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe { self.cursor.next_back(self.list.layout()).map(|item| &*item) }
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over mutable references to the data in a shsc::List.
+pub struct IterMut<'a> {
+    list: &'a List,
+    cursor: Cursor,
+    marker: PhantomData<&'a mut crate::Data>,
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = &'a mut crate::Data;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { self.cursor.next(self.list.layout()).map(|item| &mut *item) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursor.len(), Some(self.cursor.len()))
+    }
+}
+
+impl DoubleEndedIterator for IterMut<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.cursor
+                .next_back(self.list.layout())
+                .map(|item| &mut *item)
+        }
+    }
+}
+
+impl ExactSizeIterator for IterMut<'_> {}
+
+/// An iterator that moves the data out of a shsc::List.
+/// Takes over the rows of the list, which is left empty.
+pub struct IntoIter {
+    layout: Layout,
+    heap: alloc::Heap,
+    cursor: Cursor,
+}
+
+impl Iterator for IntoIter {
+    type Item = crate::Data;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { self.cursor.next(self.layout).map(|item| item.read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursor.len(), Some(self.cursor.len()))
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe { self.cursor.next_back(self.layout).map(|item| item.read()) }
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl Drop for IntoIter {
+    fn drop(&mut self) {
+        unsafe {
+            while let Some(item) = self.cursor.next(self.layout) {
+                ptr::drop_in_place(item);
+            }
+            let heap = alloc::resolve(&self.heap);
+            let Layout {
+                table, rows, cols, ..
+            } = self.layout;
+            for i in 0..rows {
+                alloc::deallocate_in::<crate::Data>(heap, *table.add(i), cols);
+            }
+            if rows > 0 {
+                alloc::deallocate_in::<*mut crate::Data>(heap, table, rows);
+            }
+        }
+    }
+}
+
+/// An iterator that removes a range of a shsc::List.
+/// Created by List::drain, the tail of the list is moved back into place when it is dropped.
+pub struct Drain<'a> {
+    list: &'a mut List,
+    cursor: Cursor,
+    end: usize,
+    tail: usize,
+}
+
+impl Iterator for Drain<'_> {
+    type Item = crate::Data;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { self.cursor.next(self.list.layout()).map(|item| item.read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursor.len(), Some(self.cursor.len()))
+    }
+}
+
+impl DoubleEndedIterator for Drain<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.cursor
+                .next_back(self.list.layout())
+                .map(|item| item.read())
+        }
+    }
+}
+
+impl ExactSizeIterator for Drain<'_> {}
+
+impl Drop for Drain<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            while let Some(item) = self.cursor.next(self.list.layout()) {
+                ptr::drop_in_place(item);
+            }
+            let start = *self.list.len;
+            List::move_block(self.list, self.end, self.list, start, self.tail);
+            *self.list.len = start + self.tail;
+            self.list.trim();
+        }
+    }
+}
+
+impl IntoIterator for List {
+    type Item = crate::Data;
+    type IntoIter = IntoIter;
+
+    /// Move the data out of the list.
+    /// If other reference copies exist, the list is copied first, as with make_mut.
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)]);
+    /// let items: Vec<shsc::Data> = list.into_iter().collect();
+    /// assert_eq!(items.len(), 2);
+    /// ```
+
+    fn into_iter(mut self) -> IntoIter {
+        self.make_mut();
+        unsafe {
+            let len = ptr::replace(self.len, 0);
+            IntoIter {
+                layout: Layout {
+                    table: ptr::replace(self.list, ptr::null_mut()),
+                    rows: ptr::replace(self.rows, 0),
+                    cols: *self.cols,
+                    first: ptr::replace(self.first, 0),
+                    head: ptr::replace(self.head, 0),
+                },
+                heap: (*self.heap).clone(),
+                cursor: Cursor::new(0, len),
+            }
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a List {
+    type Item = &'a crate::Data;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut List {
+    type Item = &'a mut crate::Data;
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+impl FromIterator<crate::Data> for List {
+    /// Collect data into a new list
+    /// ### Example
+    /// ```
+    /// let list: shsc::List = (1..=3).map(|i| shsc::todata!(i)).collect();
+    /// assert_eq!(list.len(), 3);
+    /// ```
+
+    fn from_iter<I: IntoIterator<Item = crate::Data>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl Extend<crate::Data> for List {
+    /// Append every item of an iterator to the list
+    /// ### Example
+    /// ```
+    /// let mut list = shsc::List::from(vec![shsc::todata!(1)]);
+    /// list.extend(vec![shsc::todata!(2), shsc::todata!(3)]);
+    /// assert_eq!(list.len(), 3);
+    /// ```
+
+    fn extend<I: IntoIterator<Item = crate::Data>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        if let Err(err) = self.try_make_room(iter.size_hint().0) {
+            err.abort();
+        }
+        for item in iter {
+            self.append(item);
         }
     }
 }
@@ -1,10 +1,47 @@
-use crate::traits::{self, RefC};
-use std::{collections::HashMap, ops};
+use crate::symbol::{Key, Prehashed, Symbol, Text};
+use crate::traits::{self, RefC, RefCopy};
+use crate::util::alloc::{self, AllocError};
+use crate::{debug, gc};
+use std::cmp::Ordering;
+use std::collections::{hash_map, HashMap};
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::mem::ManuallyDrop;
+use std::ops;
 
 pub struct Map {
-    map: *mut HashMap<String, usize>,
+    map: *mut Index,
     store: *mut crate::List,
     refc: *mut i64,
+    weak: *mut i64,
+}
+
+/// A non-owning handle to a shsc::Map.
+/// Does not hold a strong reference, so it does not keep the map alive.
+pub struct Weak {
+    map: *mut Index,
+    store: *mut crate::List,
+    refc: *mut i64,
+    weak: *mut i64,
+}
+
+/// The keys of a map and the store slots of their values.
+/// The index lives on the global heap, so the bytes it holds for its table
+/// and key text are charged to the allocator of the store instead.
+#[derive(Default)]
+struct Index {
+    slots: HashMap<Symbol, usize, BuildHasherDefault<Prehashed>>,
+    /// Bytes charged for the table
+    table: usize,
+    /// Bytes charged for the key text
+    text: usize,
+}
+
+impl Index {
+    /// Approximate bytes held by a table with room for capacity entries.
+    fn table_bytes(capacity: usize) -> usize {
+        // each bucket holds an entry and a control byte
+        capacity * (std::mem::size_of::<(Symbol, usize)>() + 1)
+    }
 }
 
 impl Map {
@@ -16,10 +53,13 @@ impl Map {
 
     pub fn new() -> Self {
         let map = Map {
-            map: Box::into_raw(Box::new(HashMap::new())),
-            store: Box::into_raw(Box::new(crate::List::new())),
-            refc: Box::into_raw(Box::new(0)),
+            map: Box::into_raw(Box::new(Index::default())),
+            store: Box::into_raw(Box::new(crate::List::untracked())),
+            refc: Box::into_raw(Box::new(1)),
+            weak: Box::into_raw(Box::new(0)),
         };
+        gc::track_map(&map);
+        debug::record("Map", map.addr());
         map
     }
 
@@ -36,20 +76,17 @@ impl Map {
     /// ```
 
     pub fn from(hashmap: HashMap<String, crate::Data>) -> Self {
-        let newmap = Map::new();
-        unsafe {
-            for (key, value) in hashmap {
-                let index = (*newmap.store).len();
-                (*newmap.map).insert(key, index);
-                (*newmap.store).append(value);
-            }
+        let mut newmap = Map::new();
+        for (key, value) in hashmap {
+            newmap.insert(&key, value);
         }
         newmap
     }
 
     /// Insert a key-value pair into the shsc::Map struct.
+    /// The key is interned, so maps filled on the same thread share their key text.
     /// ### Arguments
-    /// * `key` - A native str type
+    /// * `key` - A native str type or a shsc::Symbol
     /// * `value` - A shsc::Data type
     /// ### Example
     /// ```
@@ -57,22 +94,98 @@ impl Map {
     /// m.insert("key", shsc::todata!(10));
     /// ```
 
-    pub fn insert(&mut self, key: &str, value: crate::Data) {
+    pub fn insert<K: Key>(&mut self, key: K, value: crate::Data) {
+        if let Err(err) = self.try_insert(key, value) {
+            err.abort();
+        }
+    }
+
+    /// Insert a key-value pair, reporting allocation failure instead of aborting.
+    /// The memory the map holds for a new key, its share of the index and the
+    /// key text, is charged to the allocator the map was created with.
+    /// ### Arguments
+    /// * `key` - A native str type or a shsc::Symbol
+    /// * `value` - A shsc::Data type, dropped if it cannot be stored
+    /// ### Returns
+    /// An error if the map could not grow
+    /// ### Example
+    /// ```
+    /// use std::rc::Rc;
+    /// use shsc::util::alloc::{self, Metered};
+    /// let heap = Rc::new(Metered::new());
+    /// let mut m = alloc::with_allocator(heap.clone(), shsc::Map::new);
+    /// heap.set_limit(Some(0));
+    /// assert!(m.try_insert("key", shsc::todata!(1)).is_err());
+    /// heap.set_limit(None);
+    /// assert!(m.try_insert("key", shsc::todata!(1)).is_ok());
+    ///
+    /// // new keys count against the limit even when the values need no room
+    /// heap.set_limit(Some(heap.used() + 4096));
+    /// let mut inserted = 0;
+    /// while m.try_insert(format!("key {}", inserted), shsc::Data::NULL).is_ok() {
+    ///     inserted += 1;
+    /// }
+    /// assert!(inserted > 0 && heap.used() <= heap.limit().unwrap());
+    /// drop(m);
+    /// assert_eq!(heap.used(), 0);
+    /// ```
+
+    pub fn try_insert<K: Key>(&mut self, key: K, value: crate::Data) -> Result<(), AllocError> {
+        let key = key.intern();
         unsafe {
-            if (*self.map).contains_key(key) {
-                let index: usize = (*self.map)[key];
-                (*self.store)[index] = value;
-            } else {
-                let index = (*self.store).len();
-                (*self.map).insert(key.to_owned(), index);
-                (*self.store).append(value);
+            if let Some(&slot) = (*self.map).slots.get(&key) {
+                (&mut *self.store)[slot] = value;
+                return Ok(());
             }
+            (*self.store).try_make_room(1)?;
+            self.try_add_key(key)?;
+            (*self.store).append(value);
         }
+        Ok(())
+    }
+
+    /// Add a key that is not in the index, pointing at the next store slot.
+    /// Nothing is added if the heap refuses the memory for it.
+    unsafe fn try_add_key(&self, key: Symbol) -> Result<(), AllocError> {
+        let index = &mut *self.map;
+        let heap = (*self.store).allocator();
+        index
+            .slots
+            .try_reserve(1)
+            .map_err(|_| AllocError::of_size(Index::table_bytes(index.slots.len() + 1)))?;
+        // the table may have grown without being charged by an earlier failure
+        let table = Index::table_bytes(index.slots.capacity());
+        let text = key.as_str().len();
+        alloc::try_charge(heap, table - index.table + text)?;
+        index.table = table;
+        index.text += text;
+        index.slots.insert(key, (*self.store).len());
+        Ok(())
+    }
+
+    /// Find the store slot holding the value for a key.
+    fn slot<K: Key>(&self, key: &K) -> Option<usize> {
+        let slots = unsafe { &(*self.map).slots };
+        match key.symbol() {
+            Some(symbol) => slots.get(symbol).copied(),
+            None => slots.get(Text::new(key.text())).copied(),
+        }
+    }
+
+    /// Give back everything the index has charged to the heap, leaving it empty.
+    unsafe fn clear_index(&self) {
+        let index = &mut *self.map;
+        (*self.store)
+            .allocator()
+            .discharge(index.table + index.text);
+        *index = Index::default();
     }
 
     /// Get a reference to the shsc::Data type associated with a key.
+    /// Looking up by a shsc::Symbol uses the hash it carries instead of hashing
+    /// the text, and compares keys interned on the same thread by address.
     /// ### Arguments
-    /// * `key` - A native str type
+    /// * `key` - A native str type or a shsc::Symbol
     /// ### Returns
     /// A reference to the shsc::Data type associated with the key
     /// ### Example
@@ -80,16 +193,18 @@ impl Map {
     /// let mut m = shsc::Map::new();
     /// m.insert("key", shsc::todata!(10));
     /// let value = m.get("key").unwrap();
+    /// let key = shsc::Symbol::intern("key");
+    /// assert_eq!(m.get(&key).unwrap().as_i64(), Some(10));
     /// ```
 
-    pub fn get(&self, key: &str) -> Option<&crate::Data> {
+    pub fn get<K: Key>(&self, key: K) -> Option<&crate::Data> {
         unsafe {
             let store = self
                 .store
                 .as_ref()
                 .expect("shsc::Map::get: undefined data store");
-            match (*self.map).get(key) {
-                Some(&index) => store.get(index),
+            match self.slot(&key) {
+                Some(index) => store.get(index),
                 None => None,
             }
         }
@@ -97,7 +212,7 @@ impl Map {
 
     /// Get a mutable reference to the shsc::Data type associated with a key.
     /// ### Arguments
-    /// * `key` - A native str type
+    /// * `key` - A native str type or a shsc::Symbol
     /// ### Returns
     /// A mutable reference to the shsc::Data type associated with the key
     /// ### Example
@@ -108,14 +223,14 @@ impl Map {
     /// *value = shsc::todata!(20);
     /// ```
 
-    pub fn get_mut(&mut self, key: &str) -> Option<&mut crate::Data> {
+    pub fn get_mut<K: Key>(&mut self, key: K) -> Option<&mut crate::Data> {
         unsafe {
             let store = self
                 .store
                 .as_mut()
                 .expect("shsc::Map::get_mut: undefined data store");
-            match (*self.map).get(key) {
-                Some(&index) => store.get_mut(index),
+            match self.slot(&key) {
+                Some(index) => store.get_mut(index),
                 None => None,
             }
         }
@@ -124,7 +239,7 @@ impl Map {
     /// Move the shsc::Data type associated with a key out of the shsc::Map struct.
     /// Replace the shsc::Data type with shsc::Data::NULL
     /// ### Arguments
-    /// * `key` - A native str type
+    /// * `key` - A native str type or a shsc::Symbol
     /// ### Returns
     /// The shsc::Data type associated with the key
     /// ### Example
@@ -134,14 +249,14 @@ impl Map {
     /// let value = m.take("key").unwrap();
     /// ```
 
-    pub fn take(&self, key: &str) -> Option<crate::Data> {
+    pub fn take<K: Key>(&self, key: K) -> Option<crate::Data> {
         unsafe {
             let store = self
                 .store
                 .as_ref()
                 .expect("shsc::Map::get_into: undefined data store");
-            match (*self.map).get(key) {
-                Some(&index) => store.take(index),
+            match self.slot(&key) {
+                Some(index) => store.take(index),
                 None => None,
             }
         }
@@ -149,7 +264,7 @@ impl Map {
 
     /// Remove a key-value pair from the shsc::Map struct.
     /// ### Arguments
-    /// * `key` - A native str type
+    /// * `key` - A native str type or a shsc::Symbol
     /// ### Returns
     /// The shsc::Data type associated with the key
     /// ### Example
@@ -160,13 +275,243 @@ impl Map {
     /// let value = m.remove("key").unwrap();
     /// ```
 
-    pub fn remove(&mut self, key: &str) -> Option<crate::Data> {
+    pub fn remove<K: Key>(&mut self, key: K) -> Option<crate::Data> {
         unsafe {
-            match (*self.map).remove(key) {
-                Some(index) => (*self.store).take(index),
-                None => None,
+            let index = &mut *self.map;
+            let (key, slot) = match key.symbol() {
+                Some(symbol) => index.slots.remove_entry(symbol)?,
+                None => index.slots.remove_entry(Text::new(key.text()))?,
+            };
+            index.text -= key.as_str().len();
+            (*self.store).allocator().discharge(key.as_str().len());
+            (*self.store).take(slot)
+        }
+    }
+
+    /// Get the number of entries in the map
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// m.insert("a", shsc::todata!(2));
+    /// assert_eq!(m.len(), 1);
+    /// ```
+
+    pub fn len(&self) -> usize {
+        unsafe { (*self.map).slots.len() }
+    }
+
+    /// Iterate over the key-value pairs of the map, in no particular order
+    /// ### Returns
+    /// An iterator over pairs of keys and references to the data
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// m.insert("b", shsc::todata!(2));
+    /// let mut pairs: Vec<_> = m.iter().map(|(k, v)| (k, v.as_i64().unwrap())).collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, [("a", 1), ("b", 2)]);
+    /// ```
+
+    pub fn iter(&self) -> Iter<'_> {
+        unsafe {
+            Iter {
+                inner: (*self.map).slots.iter(),
+                store: &*self.store,
+            }
+        }
+    }
+
+    /// Iterate over the keys of the map, in no particular order
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// assert_eq!(m.keys().collect::<Vec<_>>(), ["a"]);
+    /// ```
+
+    pub fn keys(&self) -> Keys<'_> {
+        unsafe {
+            Keys {
+                inner: (*self.map).slots.keys(),
+            }
+        }
+    }
+
+    /// Iterate over the values of the map, in no particular order
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// m.insert("b", shsc::todata!(2));
+    /// let sum: i64 = m.values().filter_map(|v| v.as_i64()).sum();
+    /// assert_eq!(sum, 3);
+    /// ```
+
+    pub fn values(&self) -> Values<'_> {
+        Values { inner: self.iter() }
+    }
+
+    /// Build a new map from deep copies of the entries whose values match a predicate
+    /// ### Arguments
+    /// * `predicate` - Function returning true for the values to keep
+    /// ### Returns
+    /// A new map holding the matching entries
+    /// ### Example
+    /// ```
+    /// let m: shsc::Map = vec![("a", shsc::todata!(1)), ("b", shsc::todata!(-1))]
+    ///     .into_iter()
+    ///     .collect();
+    /// let positive = m.filter_values(|v| v.as_i64() > Some(0));
+    /// assert_eq!(positive.keys().collect::<Vec<_>>(), ["a"]);
+    /// ```
+
+    pub fn filter_values<F>(&self, mut predicate: F) -> Map
+    where
+        F: FnMut(&crate::Data) -> bool,
+    {
+        self.iter()
+            .filter(|(_, value)| predicate(value))
+            .map(|(key, value)| (key, value.clone()))
+            .collect()
+    }
+
+    /// Build a new map with the same keys and the result of a function on each value
+    /// ### Arguments
+    /// * `f` - Function mapping a value to new data
+    /// ### Returns
+    /// A new map holding the mapped values
+    /// ### Example
+    /// ```
+    /// let m: shsc::Map = vec![("a", shsc::todata!(1)), ("b", shsc::todata!(2))]
+    ///     .into_iter()
+    ///     .collect();
+    /// let squared = m.map_values(|v| shsc::todata!(v.as_i64().unwrap().pow(2)));
+    /// assert_eq!(squared["b"].as_i64(), Some(4));
+    /// ```
+
+    pub fn map_values<F>(&self, mut f: F) -> Map
+    where
+        F: FnMut(&crate::Data) -> crate::Data,
+    {
+        self.iter().map(|(key, value)| (key, f(value))).collect()
+    }
+
+    /// Split the map in two by a predicate on its entries
+    /// ### Arguments
+    /// * `predicate` - Function called with each key and value
+    /// ### Returns
+    /// A pair of new maps, holding deep copies of the entries that matched and those that did not
+    /// ### Example
+    /// ```
+    /// let m: shsc::Map = vec![("a", shsc::todata!(1)), ("_b", shsc::todata!(2))]
+    ///     .into_iter()
+    ///     .collect();
+    /// let (private, public) = m.partition(|key, _| key.starts_with('_'));
+    /// assert_eq!(private.len(), 1);
+    /// assert_eq!(public["a"].as_i64(), Some(1));
+    /// ```
+
+    pub fn partition<F>(&self, mut predicate: F) -> (Map, Map)
+    where
+        F: FnMut(&str, &crate::Data) -> bool,
+    {
+        let (mut matched, mut rest) = (Map::new(), Map::new());
+        for (key, value) in self.iter() {
+            if predicate(key, value) {
+                matched.insert(key, value.clone());
+            } else {
+                rest.insert(key, value.clone());
             }
         }
+        (matched, rest)
+    }
+
+    /// Get the map for mutation without affecting its reference copies.
+    /// The map is copied only if other reference copies exist, after which
+    /// this handle refers to the copy. The copy holds reference copies of
+    /// nested containers, which stay shared until they are made mutable too.
+    /// ### Returns
+    /// The map, no longer shared
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// let mut copy = m.refcopy();
+    /// copy.make_mut().insert("a", shsc::todata!(2));
+    /// assert_eq!(m["a"].as_i64(), Some(1));
+    /// assert_eq!(copy["a"].as_i64(), Some(2));
+    /// ```
+
+    pub fn make_mut(&mut self) -> &mut Map {
+        if self.getrc() > 1 {
+            let mut copy = Map::new();
+            unsafe {
+                for (key, &index) in (*self.map).slots.iter() {
+                    let value = (*self.store)
+                        .get_mut(index)
+                        .expect(&format!("shsc::Map::make_mut: invalid index {}", index));
+                    copy.insert(key, value.refcopy());
+                }
+            }
+            *self = copy;
+        }
+        self
+    }
+
+    /// Create a weak handle to the map
+    /// ### Returns
+    /// A weak handle that does not keep the map alive
+    /// ### Example
+    /// ```
+    /// let m = shsc::Map::new();
+    /// let weak = m.downgrade();
+    /// assert!(weak.upgrade().is_some());
+    /// drop(m);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+
+    pub fn downgrade(&self) -> Weak {
+        unsafe { *self.weak += 1 };
+        Weak {
+            map: self.map,
+            store: self.store,
+            refc: self.refc,
+            weak: self.weak,
+        }
+    }
+
+    /// Address identifying the map, shared by all its reference copies.
+    pub(crate) fn addr(&self) -> usize {
+        self.refc as usize
+    }
+
+    /// A handle to the map that does not own a reference.
+    /// Must not outlive the map.
+    pub(crate) unsafe fn shadow(&self) -> ManuallyDrop<Map> {
+        ManuallyDrop::new(std::ptr::read(self))
+    }
+
+    /// Drop every entry, leaving the map empty.
+    pub(crate) fn release(&mut self) {
+        unsafe {
+            self.clear_index();
+            (*self.store).release();
+        }
+    }
+
+    /// Key-value pairs sorted by key, giving a canonical order for comparison and hashing.
+    fn sorted_entries(&self) -> Vec<(&str, &crate::Data)> {
+        let mut entries = self.entries();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+
+    /// Key-value pairs in index map order, for crate internal traversal.
+    pub(crate) fn entries(&self) -> Vec<(&str, &crate::Data)> {
+        self.iter().collect()
     }
 }
 
@@ -210,6 +555,43 @@ impl ops::IndexMut<&str> for Map {
     }
 }
 
+impl ops::Index<&Symbol> for Map {
+    type Output = crate::Data;
+
+    /// Get a reference to the shsc::Data type associated with a symbol key.
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("key", shsc::todata!(10));
+    /// let key = shsc::Symbol::intern("key");
+    /// assert_eq!(m[&key].as_i64(), Some(10));
+    /// ```
+
+    fn index(&self, key: &Symbol) -> &crate::Data {
+        self.get(key)
+            .expect(&format!("shsc::Map::index: invalid key {}", key.as_str()))
+    }
+}
+
+impl ops::IndexMut<&Symbol> for Map {
+    /// Get a mutable reference to the shsc::Data type associated with a symbol key.
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("key", shsc::todata!(10));
+    /// let key = shsc::Symbol::intern("key");
+    /// m[&key] = shsc::todata!(20);
+    /// assert_eq!(m["key"].as_i64(), Some(20));
+    /// ```
+
+    fn index_mut(&mut self, key: &Symbol) -> &mut crate::Data {
+        self.get_mut(key).expect(&format!(
+            "shsc::Map::index_mut: invalid key {}",
+            key.as_str()
+        ))
+    }
+}
+
 impl traits::ToStr for Map {
     /// Get a string representation of the shsc::Map struct.
     /// ### Returns
@@ -228,9 +610,11 @@ impl traits::ToStr for Map {
 
     fn tostr(&self) -> String {
         let map = unsafe {
-            self.map
+            &self
+                .map
                 .as_ref()
                 .expect("shsc::Map::tostr: undefined index map")
+                .slots
         };
         let store = unsafe {
             self.store
@@ -245,12 +629,12 @@ impl traits::ToStr for Map {
             if value.is_null() {
                 continue;
             }
-            result.push_str(&format!("{}: {}", key, value.tostr()));
+            result.push_str(&format!("{}: {}", key.as_str(), value.tostr()));
             if i < store.len() - 1 {
                 result.push_str(", ");
             }
         }
-        result.push_str("}");
+        result.push('}');
         result
     }
 }
@@ -273,6 +657,7 @@ impl traits::RefCopy for Map {
             map: self.map,
             store: self.store,
             refc: self.refc,
+            weak: self.weak,
         }
     }
 
@@ -313,15 +698,13 @@ impl Clone for Map {
                 .store
                 .as_ref()
                 .expect("shsc::Map::clone: undefined data store");
-            for (key, &index) in (*self.map).iter() {
+            for (key, &index) in (*self.map).slots.iter() {
                 let value = oldstore
                     .get(index)
                     .expect(&format!("shsc::Map::clone: invalid index {}", index));
-                newmap
-                    .map
-                    .as_mut()
-                    .unwrap()
-                    .insert(key.clone(), (*newmap.store).len());
+                if let Err(err) = newmap.try_add_key(key.clone()) {
+                    err.abort();
+                }
                 (*newmap.store).append(value.clone());
             }
         }
@@ -329,6 +712,60 @@ impl Clone for Map {
     }
 }
 
+impl PartialEq for Map {
+    /// Implement the PartialEq trait for the Map struct.
+    /// Maps are equal if they have the same keys mapped to equal values.
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// m.insert("b", shsc::todata!(2));
+    /// let mut m2 = shsc::Map::new();
+    /// m2.insert("b", shsc::todata!(2));
+    /// m2.insert("a", shsc::todata!(1));
+    /// assert!(m == m2);
+    /// ```
+
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Map {}
+
+impl PartialOrd for Map {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Map {
+    /// Implement the Ord trait for the Map struct.
+    /// Maps compare lexicographically by their key-value pairs sorted by key.
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// let mut m2 = shsc::Map::new();
+    /// m2.insert("a", shsc::todata!(2));
+    /// assert!(m < m2);
+    /// ```
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reference copies of one map are trivially equal
+        if self.refc == other.refc {
+            return Ordering::Equal;
+        }
+        self.sorted_entries().cmp(&other.sorted_entries())
+    }
+}
+
+impl Hash for Map {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sorted_entries().hash(state);
+    }
+}
+
 impl traits::RefC for Map {
     fn incrc(&mut self) {
         unsafe { (*self.refc) += 1 };
@@ -361,10 +798,221 @@ impl Drop for Map {
         if self.getrc() > 0 {
             return;
         }
+        gc::untrack(self.addr());
+        debug::forget(self.addr());
         unsafe {
+            // the values may hold weak handles to this map,
+            // so keep the counters alive until they are dropped
+            *self.weak += 1;
+            self.clear_index();
             drop(Box::from_raw(self.map));
             drop(Box::from_raw(self.store));
-            drop(Box::from_raw(self.refc));
+            *self.weak -= 1;
+            if (*self.weak) == 0 {
+                drop(Box::from_raw(self.refc));
+                drop(Box::from_raw(self.weak));
+            }
+        }
+    }
+}
+
+impl Weak {
+    /// Get a strong reference to the map if it is still alive
+    /// ### Returns
+    /// A reference copy of the map, or None if the map has been deallocated
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// let mut parent = shsc::Map::new();
+    /// let mut child = shsc::Map::new();
+    /// child.insert("parent", shsc::todata!(parent.downgrade()));
+    /// parent.insert("child", shsc::todata!(child.refcopy()));
+    /// let up = child["parent"].as_weak().unwrap().upgrade().unwrap();
+    /// assert!(up.as_map().unwrap().get("child").is_some());
+    /// drop(up);
+    /// drop(parent);
+    /// assert!(child["parent"].as_weak().unwrap().upgrade().is_none());
+    /// ```
+
+    pub fn upgrade(&self) -> Option<Map> {
+        unsafe {
+            if (*self.refc) <= 0 {
+                return None;
+            }
+            *self.refc += 1;
+        }
+        Some(Map {
+            map: self.map,
+            store: self.store,
+            refc: self.refc,
+            weak: self.weak,
+        })
+    }
+
+    /// Get the number of strong references to the map
+    /// ### Returns
+    /// The strong reference count, zero once the map has been deallocated
+    /// ### Example
+    /// ```
+    /// let m = shsc::Map::new();
+    /// let weak = m.downgrade();
+    /// assert_eq!(weak.strong_count(), 1);
+    /// drop(m);
+    /// assert_eq!(weak.strong_count(), 0);
+    /// ```
+
+    pub fn strong_count(&self) -> i64 {
+        unsafe { *self.refc }
+    }
+
+    /// Check whether two weak handles point to the same map
+    /// ### Example
+    /// ```
+    /// let m = shsc::Map::new();
+    /// assert!(m.downgrade().ptr_eq(&m.downgrade()));
+    /// ```
+
+    pub fn ptr_eq(&self, other: &Weak) -> bool {
+        self.refc == other.refc
+    }
+
+    pub(crate) fn addr(&self) -> usize {
+        self.refc as usize
+    }
+}
+
+impl Clone for Weak {
+    /// Implement the Clone trait for the Weak struct.
+    /// Results in another weak handle to the same map.
+    fn clone(&self) -> Self {
+        unsafe { *self.weak += 1 };
+        Weak {
+            map: self.map,
+            store: self.store,
+            refc: self.refc,
+            weak: self.weak,
+        }
+    }
+}
+
+impl Drop for Weak {
+    /// Implement the Drop trait for the Weak struct.
+    /// Frees the shared counters once no strong or weak references remain.
+    fn drop(&mut self) {
+        unsafe {
+            *self.weak -= 1;
+            if (*self.weak) == 0 && (*self.refc) <= 0 {
+                drop(Box::from_raw(self.refc));
+                drop(Box::from_raw(self.weak));
+            }
+        }
+    }
+}
+
+/// An iterator over the key-value pairs of a shsc::Map.
+pub struct Iter<'a> {
+    inner: hash_map::Iter<'a, Symbol, usize>,
+    store: &'a crate::List,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a crate::Data);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, &index)| {
+            let value = self
+                .store
+                .get(index)
+                .expect(&format!("shsc::Map::iter: invalid index {}", index));
+            (key.as_str(), value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over the keys of a shsc::Map.
+pub struct Keys<'a> {
+    inner: hash_map::Keys<'a, Symbol, usize>,
+}
+
+impl<'a> Iterator for Keys<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(Symbol::as_str)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Keys<'_> {}
+
+/// An iterator over the values of a shsc::Map.
+pub struct Values<'a> {
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for Values<'a> {
+    type Item = &'a crate::Data;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Values<'_> {}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a str, &'a crate::Data);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<K: Key> FromIterator<(K, crate::Data)> for Map {
+    /// Collect key-value pairs into a new map
+    /// ### Example
+    /// ```
+    /// let m: shsc::Map = vec![("a", shsc::todata!(1)), ("b", shsc::todata!(2))]
+    ///     .into_iter()
+    ///     .collect();
+    /// assert_eq!(m["b"].as_i64(), Some(2));
+    /// ```
+
+    fn from_iter<I: IntoIterator<Item = (K, crate::Data)>>(iter: I) -> Self {
+        let mut map = Map::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Key> Extend<(K, crate::Data)> for Map {
+    /// Insert every key-value pair of an iterator, replacing existing values
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("a", shsc::todata!(1));
+    /// m.extend(vec![("a", shsc::todata!(2)), ("b", shsc::todata!(3))]);
+    /// assert_eq!(m["a"].as_i64(), Some(2));
+    /// assert_eq!(m.len(), 2);
+    /// ```
+
+    fn extend<I: IntoIterator<Item = (K, crate::Data)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
         }
     }
 }
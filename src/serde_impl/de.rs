@@ -0,0 +1,469 @@
+use super::Error;
+use crate::traits::ToStr;
+use serde::de::{self, Deserialize, IntoDeserializer};
+use std::fmt;
+
+struct DataVisitor;
+
+impl<'de> de::Visitor<'de> for DataVisitor {
+    type Value = crate::Data;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value representable as shsc::Data")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_i8<E: de::Error>(self, v: i8) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_i16<E: de::Error>(self, v: i16) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_i32<E: de::Error>(self, v: i32) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<crate::Data, E> {
+        if let Ok(v) = i64::try_from(v) {
+            return Ok(crate::todata!(v));
+        }
+        match u64::try_from(v) {
+            Ok(v) => Ok(crate::todata!(v)),
+            Err(_) => Err(E::custom("i128 value out of range for INT64 and UINT64")),
+        }
+    }
+
+    fn visit_u8<E: de::Error>(self, v: u8) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_u16<E: de::Error>(self, v: u16) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_u32<E: de::Error>(self, v: u32) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<crate::Data, E> {
+        match u64::try_from(v) {
+            Ok(v) => Ok(crate::todata!(v)),
+            Err(_) => Err(E::custom("u128 value out of range for UINT64")),
+        }
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v as f64))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<crate::Data, E> {
+        Ok(crate::todata!(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<crate::Data, E> {
+        Ok(crate::Data::NULL)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<crate::Data, D::Error> {
+        crate::Data::deserialize(d)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<crate::Data, E> {
+        Ok(crate::Data::NULL)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<crate::Data, D::Error> {
+        crate::Data::deserialize(d)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<crate::Data, A::Error> {
+        let mut list = crate::List::new();
+        while let Some(item) = seq.next_element::<crate::Data>()? {
+            list.append(item);
+        }
+        Ok(crate::todata!(list))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut access: A) -> Result<crate::Data, A::Error> {
+        let mut map = crate::Map::new();
+        while let Some(key) = access.next_key::<crate::Data>()? {
+            let key = match &key.data {
+                crate::DataTypes::STRING(value) => value.tostr(),
+                crate::DataTypes::MAP(_)
+                | crate::DataTypes::LIST(_)
+                | crate::DataTypes::BYTES(_) => {
+                    let message = format!("map key must be a string, found {}", key.typename());
+                    return Err(de::Error::custom(message));
+                }
+                _ => key.tostr(),
+            };
+            map.insert(&key, access.next_value::<crate::Data>()?);
+        }
+        Ok(crate::todata!(map))
+    }
+}
+
+impl<'de> Deserialize<'de> for crate::Data {
+    /// Deserialize shsc::Data through serde, keeping integer widths reported by the format.
+    /// ### Example
+    /// ```
+    /// let data: shsc::Data = shsc::from_data(&shsc::todata!(vec![shsc::todata!(1u16)])).unwrap();
+    /// assert_eq!(data.as_list().unwrap()[0].typename(), "UINT16");
+    /// ```
+
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(DataVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for crate::String {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let data = crate::Data::deserialize(d)?;
+        match data.data {
+            crate::DataTypes::STRING(value) => Ok(value),
+            _ => Err(expected::<D::Error>("STRING", &data)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for crate::Bytes {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let data = crate::Data::deserialize(d)?;
+        match data.data {
+            crate::DataTypes::BYTES(value) => Ok(value),
+            _ => Err(expected::<D::Error>("BYTES", &data)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for crate::List {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let data = crate::Data::deserialize(d)?;
+        match data.data {
+            crate::DataTypes::LIST(value) => Ok(value),
+            _ => Err(expected::<D::Error>("LIST", &data)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for crate::Map {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let data = crate::Data::deserialize(d)?;
+        match data.data {
+            crate::DataTypes::MAP(value) => Ok(value),
+            _ => Err(expected::<D::Error>("MAP", &data)),
+        }
+    }
+}
+
+fn expected<E: de::Error>(typename: &str, found: &crate::Data) -> E {
+    E::custom(format!("expected {}, found {}", typename, found.typename()))
+}
+
+/// A serde Deserializer reading from a shsc::Data tree, see shsc::from_data.
+pub struct DataDeserializer<'a> {
+    data: &'a crate::Data,
+}
+
+impl<'a> DataDeserializer<'a> {
+    pub fn new(data: &'a crate::Data) -> Self {
+        DataDeserializer { data }
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for DataDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self.data.data {
+            crate::DataTypes::BOOL(value) => visitor.visit_bool(*value),
+            crate::DataTypes::UINT8(value) => visitor.visit_u8(*value),
+            crate::DataTypes::UINT16(value) => visitor.visit_u16(*value),
+            crate::DataTypes::UINT32(value) => visitor.visit_u32(*value),
+            crate::DataTypes::UINT64(value) => visitor.visit_u64(*value),
+            crate::DataTypes::INT8(value) => visitor.visit_i8(*value),
+            crate::DataTypes::INT16(value) => visitor.visit_i16(*value),
+            crate::DataTypes::INT32(value) => visitor.visit_i32(*value),
+            crate::DataTypes::INT64(value) => visitor.visit_i64(*value),
+            crate::DataTypes::FLOAT64(value) => visitor.visit_f64(*value),
+            crate::DataTypes::CHAR(value) => visitor.visit_char(*value),
+            crate::DataTypes::STRING(value) => visitor.visit_string(value.tostr()),
+            crate::DataTypes::BYTES(value) => visitor.visit_bytes(value.as_slice()),
+            crate::DataTypes::LIST(value) => {
+                visitor.visit_seq(SeqDeserializer { iter: value.iter() })
+            }
+            crate::DataTypes::MAP(value) => visitor.visit_map(MapDeserializer {
+                iter: value.iter(),
+                value: None,
+            }),
+            crate::DataTypes::NULL => visitor.visit_unit(),
+            crate::DataTypes::FUNCTION(_)
+            | crate::DataTypes::USERDATA(_)
+            | crate::DataTypes::WEAK(_) => Err(Error {
+                message: format!("cannot deserialize from {}", self.data.typename()),
+            }),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.data.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match &self.data.data {
+            crate::DataTypes::STRING(value) => visitor.visit_enum(EnumDeserializer {
+                variant: value.tostr(),
+                value: None,
+            }),
+            crate::DataTypes::MAP(value) => {
+                let mut entries = value.entries();
+                if entries.len() != 1 {
+                    return Err(Error {
+                        message: "expected a MAP with a single key for an enum".to_owned(),
+                    });
+                }
+                let (variant, value) = entries.remove(0);
+                visitor.visit_enum(EnumDeserializer {
+                    variant: variant.to_owned(),
+                    value: Some(value),
+                })
+            }
+            _ => Err(expected("STRING or MAP for an enum", self.data)),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+struct SeqDeserializer<'a> {
+    iter: crate::list::Iter<'a>,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for SeqDeserializer<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.iter.next() {
+            Some(item) => seed.deserialize(DataDeserializer::new(item)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer<'a> {
+    iter: crate::map::Iter<'a>,
+    value: Option<&'a crate::Data>,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for MapDeserializer<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(KeyDeserializer { key }).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .expect("shsc::serde: next_value called before next_key");
+        seed.deserialize(DataDeserializer::new(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Deserializes MAP keys, parsing them back into integers or bools when asked,
+/// since the serializer stores those keys as text.
+struct KeyDeserializer<'a> {
+    key: &'a str,
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.key.parse::<$ty>() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => visitor.visit_str(self.key),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for KeyDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.key)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.key.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        f32 f64 char str string bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer<'a> {
+    variant: std::string::String,
+    value: Option<&'a crate::Data>,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for EnumDeserializer<'a> {
+    type Error = Error;
+    type Variant = VariantDeserializer<'a>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer<'a>), Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer<'a> {
+    value: Option<&'a crate::Data>,
+}
+
+impl<'a> VariantDeserializer<'a> {
+    fn value(self) -> Result<DataDeserializer<'a>, Error> {
+        match self.value {
+            Some(value) => Ok(DataDeserializer::new(value)),
+            None => Err(Error {
+                message: "expected enum variant data, found a unit variant".to_owned(),
+            }),
+        }
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for VariantDeserializer<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            Some(value) if !value.is_null() => Err(expected("NULL for a unit variant", value)),
+            _ => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self.value()?, visitor)
+    }
+}
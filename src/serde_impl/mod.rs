@@ -0,0 +1,89 @@
+pub mod de;
+pub mod ser;
+
+use std::fmt;
+
+/// Error returned when converting between serde types and shsc::Data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub message: std::string::String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "shsc::serde: {}", self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error {
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error {
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Convert any serde serializable value into a shsc::Data tree.
+///
+/// Integer widths are kept, so an `i8` field becomes INT8 and a `u64` field UINT64.
+/// Structs and maps become MAP, sequences and tuples LIST, `None` and `()` NULL.
+/// Enums follow serde's externally tagged layout: a unit variant becomes a STRING
+/// holding its name and other variants a MAP with the name as the only key.
+/// ### Arguments
+/// * `value` - A serde serializable value
+/// ### Returns
+/// The value as shsc::Data
+/// ### Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Point {
+///     x: i8,
+///     y: u64,
+/// }
+/// let data = shsc::to_data(&Point { x: -1, y: 2 }).unwrap();
+/// let map = data.as_map().unwrap();
+/// assert_eq!(map["x"].typename(), "INT8");
+/// assert_eq!(map["y"].typename(), "UINT64");
+/// ```
+
+pub fn to_data<T>(value: &T) -> Result<crate::Data, Error>
+where
+    T: serde::Serialize + ?Sized,
+{
+    value.serialize(ser::DataSerializer)
+}
+
+/// Convert a shsc::Data tree into any serde deserializable value.
+/// Integers convert to any integer type they fit in.
+/// ### Arguments
+/// * `data` - The data to convert
+/// ### Returns
+/// The deserialized value
+/// ### Example
+/// ```
+/// #[derive(serde::Deserialize, Debug, PartialEq)]
+/// enum Shape {
+///     Circle { r: f64 },
+///     Empty,
+/// }
+/// let data = shsc::json::parse(r#"[{"Circle": {"r": 1.5}}, "Empty"]"#).unwrap();
+/// let shapes: Vec<Shape> = shsc::from_data(&data).unwrap();
+/// assert_eq!(shapes, vec![Shape::Circle { r: 1.5 }, Shape::Empty]);
+/// ```
+
+pub fn from_data<T>(data: &crate::Data) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    T::deserialize(de::DataDeserializer::new(data))
+}
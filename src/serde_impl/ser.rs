@@ -0,0 +1,410 @@
+use super::Error;
+use crate::traits::ToStr;
+use serde::ser::{self, Serialize};
+
+impl Serialize for crate::Data {
+    /// Serialize shsc::Data through serde, keeping the integer width of each variant.
+    /// ### Example
+    /// ```
+    /// let data = shsc::json::parse("[1, 2.5]").unwrap();
+    /// let back: shsc::Data = shsc::to_data(&data).unwrap();
+    /// assert!(back == data);
+    /// ```
+
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.data {
+            crate::DataTypes::BOOL(value) => serializer.serialize_bool(*value),
+            crate::DataTypes::UINT8(value) => serializer.serialize_u8(*value),
+            crate::DataTypes::UINT16(value) => serializer.serialize_u16(*value),
+            crate::DataTypes::UINT32(value) => serializer.serialize_u32(*value),
+            crate::DataTypes::UINT64(value) => serializer.serialize_u64(*value),
+            crate::DataTypes::INT8(value) => serializer.serialize_i8(*value),
+            crate::DataTypes::INT16(value) => serializer.serialize_i16(*value),
+            crate::DataTypes::INT32(value) => serializer.serialize_i32(*value),
+            crate::DataTypes::INT64(value) => serializer.serialize_i64(*value),
+            crate::DataTypes::FLOAT64(value) => serializer.serialize_f64(*value),
+            crate::DataTypes::CHAR(value) => serializer.serialize_char(*value),
+            crate::DataTypes::STRING(value) => value.serialize(serializer),
+            crate::DataTypes::BYTES(value) => value.serialize(serializer),
+            crate::DataTypes::LIST(value) => value.serialize(serializer),
+            crate::DataTypes::MAP(value) => value.serialize(serializer),
+            crate::DataTypes::NULL => serializer.serialize_unit(),
+            crate::DataTypes::FUNCTION(_)
+            | crate::DataTypes::USERDATA(_)
+            | crate::DataTypes::WEAK(_) => Err(ser::Error::custom(format!(
+                "cannot serialize {}",
+                self.typename()
+            ))),
+        }
+    }
+}
+
+impl Serialize for crate::String {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.tostr())
+    }
+}
+
+impl Serialize for crate::Bytes {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+impl Serialize for crate::List {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl Serialize for crate::Map {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self)
+    }
+}
+
+/// A serde Serializer whose output is a shsc::Data tree, see shsc::to_data.
+pub struct DataSerializer;
+
+impl ser::Serializer for DataSerializer {
+    type Ok = crate::Data;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = VariantSerializer<MapSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<crate::Data, Error> {
+        if let Ok(v) = i64::try_from(v) {
+            return Ok(crate::todata!(v));
+        }
+        self.serialize_u128(u128::try_from(v).map_err(|_| overflow("i128"))?)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<crate::Data, Error> {
+        let v = u64::try_from(v).map_err(|_| overflow("u128"))?;
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v as f64))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(v))
+    }
+
+    fn serialize_none(self) -> Result<crate::Data, Error> {
+        Ok(crate::Data::NULL)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<crate::Data, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<crate::Data, Error> {
+        Ok(crate::Data::NULL)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<crate::Data, Error> {
+        Ok(crate::Data::NULL)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<crate::Data, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<crate::Data, Error> {
+        let mut map = crate::Map::new();
+        map.insert(variant, value.serialize(self)?);
+        Ok(crate::todata!(map))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer {
+            list: crate::List::new(),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<SeqSerializer>, Error> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer {
+            map: crate::Map::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<MapSerializer>, Error> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+fn overflow(typename: &str) -> Error {
+    Error {
+        message: format!("{} value out of range for INT64 and UINT64", typename),
+    }
+}
+
+/// Collects sequences and tuples into a LIST.
+pub struct SeqSerializer {
+    list: crate::List,
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = crate::Data;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.list.append(value.serialize(DataSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(self.list))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = crate::Data;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<crate::Data, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = crate::Data;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<crate::Data, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Collects maps and structs into a MAP.
+/// Keys must serialize to a STRING, CHAR, BOOL or integer, which are stored as text.
+pub struct MapSerializer {
+    map: crate::Map,
+    key: Option<std::string::String>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = crate::Data;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = key.serialize(DataSerializer)?;
+        match &key.data {
+            crate::DataTypes::STRING(_)
+            | crate::DataTypes::CHAR(_)
+            | crate::DataTypes::BOOL(_)
+            | crate::DataTypes::UINT8(_)
+            | crate::DataTypes::UINT16(_)
+            | crate::DataTypes::UINT32(_)
+            | crate::DataTypes::UINT64(_)
+            | crate::DataTypes::INT8(_)
+            | crate::DataTypes::INT16(_)
+            | crate::DataTypes::INT32(_)
+            | crate::DataTypes::INT64(_) => {
+                self.key = Some(key.tostr());
+                Ok(())
+            }
+            _ => Err(Error {
+                message: format!("map key must be a string, found {}", key.typename()),
+            }),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .expect("shsc::serde: serialize_value called before serialize_key");
+        self.map.insert(&key, value.serialize(DataSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<crate::Data, Error> {
+        Ok(crate::todata!(self.map))
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = crate::Data;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.map.insert(key, value.serialize(DataSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<crate::Data, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Wraps the LIST or MAP of a tuple or struct variant in a MAP keyed by the variant name.
+pub struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn wrap(variant: &'static str, value: crate::Data) -> crate::Data {
+        let mut map = crate::Map::new();
+        map.insert(variant, value);
+        crate::todata!(map)
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = crate::Data;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<crate::Data, Error> {
+        let value = ser::SerializeSeq::end(self.inner)?;
+        Ok(Self::wrap(self.variant, value))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<MapSerializer> {
+    type Ok = crate::Data;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<crate::Data, Error> {
+        let value = ser::SerializeMap::end(self.inner)?;
+        Ok(Self::wrap(self.variant, value))
+    }
+}
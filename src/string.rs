@@ -1,20 +1,116 @@
 use crate::traits::{self, Append, RefC};
+use crate::util::alloc::{self, AllocError};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ptr;
+
+const INLINE: usize = 22;
 
 pub struct String {
-    str: crate::List,
+    buf: *mut Buf,
+    len: *mut usize,
+    heap: *mut alloc::Heap,
+    refc: *mut i64,
+}
+
+/// UTF-8 bytes of a string, kept inline until they outgrow INLINE bytes.
+enum Buf {
+    Inline {
+        bytes: [u8; INLINE],
+        len: u8,
+    },
+    Heap {
+        ptr: *mut u8,
+        cap: usize,
+        len: usize,
+    },
+}
+
+impl Buf {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            Buf::Inline { bytes, len } => &bytes[..*len as usize],
+            Buf::Heap { ptr, len, .. } => unsafe { std::slice::from_raw_parts(*ptr, *len) },
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        match self {
+            Buf::Inline { .. } => INLINE,
+            Buf::Heap { cap, .. } => *cap,
+        }
+    }
+
+    /// Make room for at least needed bytes, moving to the heap if necessary.
+    fn try_reserve(
+        &mut self,
+        heap: &dyn alloc::Allocator,
+        needed: usize,
+    ) -> Result<(), AllocError> {
+        let cap = self.capacity();
+        if needed <= cap {
+            return Ok(());
+        }
+        let newcap = needed.max(cap * 2);
+        match self {
+            Buf::Inline { bytes, len } => {
+                let ptr = alloc::try_allocate_in::<u8>(heap, newcap)?;
+                unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, *len as usize) };
+                *self = Buf::Heap {
+                    ptr,
+                    cap: newcap,
+                    len: *len as usize,
+                };
+            }
+            Buf::Heap { ptr, cap, .. } => {
+                *ptr = alloc::try_reallocate_in::<u8>(heap, *ptr, *cap, newcap)?;
+                *cap = newcap;
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert bytes at a byte offset, room must have been reserved.
+    unsafe fn insert(&mut self, at: usize, value: &[u8]) {
+        let (base, len) = match self {
+            Buf::Inline { bytes, len } => (bytes.as_mut_ptr(), *len as usize),
+            Buf::Heap { ptr, len, .. } => (*ptr, *len),
+        };
+        ptr::copy(base.add(at), base.add(at + value.len()), len - at);
+        ptr::copy_nonoverlapping(value.as_ptr(), base.add(at), value.len());
+        match self {
+            Buf::Inline { len, .. } => *len += value.len() as u8,
+            Buf::Heap { len, .. } => *len += value.len(),
+        }
+    }
+
+    fn release(&mut self, heap: &dyn alloc::Allocator) {
+        if let Buf::Heap { ptr, cap, .. } = self {
+            alloc::deallocate_in::<u8>(heap, *ptr, *cap);
+        }
+    }
 }
 
 impl String {
     /// Create a new shsc::String struct.
+    /// Strings of up to 22 bytes are stored without a separate buffer.
     /// ### Example
     /// ```
     /// let s = shsc::String::new();
     /// ```
 
     pub fn new() -> Self {
-        crate::String {
-            str: crate::List::new(),
-        }
+        let str = crate::String {
+            buf: Box::into_raw(Box::new(Buf::Inline {
+                bytes: [0; INLINE],
+                len: 0,
+            })),
+            len: Box::into_raw(Box::new(0)),
+            heap: Box::into_raw(Box::new(alloc::current())),
+            refc: Box::into_raw(Box::new(1)),
+        };
+        crate::debug::record("String", str.addr());
+        str
     }
 
     /// Create a new shsc::String struct from a native str type.
@@ -28,50 +124,176 @@ impl String {
     /// ```
 
     pub fn from(value: &str) -> Self {
-        let mut list = crate::List::new();
-        for c in value.chars() {
-            list.append(crate::todata!(c));
+        let mut str = crate::String::new();
+        str.append(value);
+        str
+    }
+
+    /// Get the string for mutation without affecting its reference copies.
+    /// The contents are copied only if other reference copies exist,
+    /// after which this handle refers to the copy.
+    /// ### Returns
+    /// The string, no longer shared
+    /// ### Example
+    /// ```
+    /// use shsc::traits::{Append, RefCopy, ToStr};
+    /// let mut s = shsc::String::from("hello");
+    /// let mut copy = s.refcopy();
+    /// copy.make_mut().append(" world");
+    /// assert_eq!(s.tostr(), "hello");
+    /// assert_eq!(copy.tostr(), "hello world");
+    /// ```
+
+    pub fn make_mut(&mut self) -> &mut crate::String {
+        if self.getrc() > 1 {
+            *self = self.clone();
         }
-        crate::String { str: list }
+        self
+    }
+
+    /// Append a native str type, reporting allocation failure instead of aborting.
+    /// Nothing is appended if the string cannot grow.
+    /// ### Arguments
+    /// * `value` - A native str type
+    /// ### Returns
+    /// An error if the string could not grow
+    /// ### Example
+    /// ```
+    /// use std::rc::Rc;
+    /// use shsc::traits::ToStr;
+    /// use shsc::util::alloc::{self, Metered};
+    /// let heap = Rc::new(Metered::new());
+    /// let mut s = alloc::with_allocator(heap.clone(), || shsc::String::from("hi"));
+    /// heap.set_limit(Some(heap.used()));
+    /// assert!(s.try_append(&"!".repeat(100)).is_err());
+    /// assert_eq!(s.tostr(), "hi");
+    /// ```
+
+    pub fn try_append(&mut self, value: &str) -> Result<(), AllocError> {
+        self.try_reserve(value.len())?;
+        self.append(value);
+        Ok(())
     }
 
     /// Get the length of the String struct.
     /// ### Returns
-    /// The length of the String struct
+    /// The length of the String struct in chars
     /// ### Example
     /// ```
-    /// let s = shsc::String::from("hello");
-    /// let len = s.len();
+    /// let s = shsc::String::from("héllo");
+    /// assert_eq!(s.len(), 5);
     /// ```
 
     pub fn len(&self) -> usize {
-        self.str.len()
+        unsafe { *self.len }
     }
 
-    /// Get a vector of references to the data in the shsc::String struct.
-    /// ### Returns
-    /// A vector of references to the data in the shsc::String struct
+    /// Get the length of the String struct in UTF-8 bytes.
+    /// ### Example
+    /// ```
+    /// let s = shsc::String::from("héllo");
+    /// assert_eq!(s.byte_len(), 6);
+    /// ```
+
+    pub fn byte_len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    /// Borrow the String struct as a native str type.
     /// ### Example
     /// ```
     /// let s = shsc::String::from("hello");
-    /// let vec = s.as_vec();
+    /// assert_eq!(s.as_str(), "hello");
+    /// ```
+
+    pub fn as_str(&self) -> &str {
+        // only ever filled from str values, at char boundaries
+        unsafe { std::str::from_utf8_unchecked((*self.buf).as_bytes()) }
+    }
+
+    /// Iterate over the chars of the String struct without copying it.
+    /// ### Example
+    /// ```
+    /// let s = shsc::String::from("héllo");
+    /// assert_eq!(s.chars().nth(1), Some('é'));
+    /// assert_eq!(s.chars().rev().next(), Some('o'));
     /// ```
 
-    pub fn as_vec(&self) -> Vec<&crate::Data> {
-        self.str.as_vec()
+    pub fn chars(&self) -> std::str::Chars<'_> {
+        self.as_str().chars()
     }
 
-    /// Get a vector of mutable references to the data in the shsc::String struct.
+    /// Get the char at a char index.
+    /// ### Arguments
+    /// * `index` - The char index
     /// ### Returns
-    /// A vector of mutable references to the data in the shsc::String struct
+    /// The char, or None if the index is out of bounds
     /// ### Example
     /// ```
-    /// let mut s = shsc::String::from("hello");
-    /// let vec = s.as_vec_mut();
+    /// let s = shsc::String::from("héllo");
+    /// assert_eq!(s.char_at(1), Some('é'));
+    /// assert_eq!(s.char_at(5), None);
+    /// ```
+
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        let offset = self.char_to_byte(index)?;
+        self.as_str()[offset..].chars().next()
+    }
+
+    /// Map a char index to the byte offset where that char starts.
+    /// The length in chars maps to the length in bytes.
+    /// ### Arguments
+    /// * `index` - The char index
+    /// ### Returns
+    /// The byte offset, or None if the index is past the end
+    /// ### Example
+    /// ```
+    /// let s = shsc::String::from("héllo");
+    /// assert_eq!(s.char_to_byte(2), Some(3));
+    /// assert_eq!(s.char_to_byte(5), Some(6));
+    /// assert_eq!(s.char_to_byte(6), None);
+    /// ```
+
+    pub fn char_to_byte(&self, index: usize) -> Option<usize> {
+        let str = self.as_str();
+        if index > self.len() {
+            return None;
+        }
+        // every char is one byte in ASCII strings, and the end is known,
+        // so appending does not walk the string
+        if str.len() == self.len() {
+            return Some(index);
+        }
+        if index == self.len() {
+            return Some(str.len());
+        }
+        match str.char_indices().nth(index) {
+            Some((offset, _)) => Some(offset),
+            None => Some(str.len()),
+        }
+    }
+
+    /// Map a byte offset to the index of the char starting there.
+    /// ### Arguments
+    /// * `offset` - The byte offset
+    /// ### Returns
+    /// The char index, or None if the offset is past the end or inside a char
+    /// ### Example
+    /// ```
+    /// let s = shsc::String::from("héllo");
+    /// assert_eq!(s.byte_to_char(3), Some(2));
+    /// assert_eq!(s.byte_to_char(2), None);
     /// ```
 
-    pub fn as_vec_mut(&mut self) -> Vec<&mut crate::Data> {
-        self.str.as_vec_mut()
+    pub fn byte_to_char(&self, offset: usize) -> Option<usize> {
+        let str = self.as_str();
+        if !str.is_char_boundary(offset) {
+            return None;
+        }
+        if str.len() == self.len() {
+            return Some(offset);
+        }
+        Some(str[..offset].chars().count())
     }
 
     /// Concatenate two shsc::String structs.
@@ -84,6 +306,7 @@ impl String {
     /// let s = shsc::String::from("hello");
     /// let s2 = shsc::String::from(" world");
     /// let s3 = s.concat(&s2);
+    /// assert_eq!(s3.as_str(), "hello world");
     /// ```
 
     pub fn concat(&self, other: &crate::String) -> crate::String {
@@ -92,6 +315,33 @@ impl String {
         newstr.append(other);
         newstr
     }
+
+    /// Address identifying the string, shared by all its reference copies.
+    pub(crate) fn addr(&self) -> usize {
+        self.refc as usize
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        unsafe {
+            let needed = (*self.buf).as_bytes().len() + additional;
+            (*self.buf).try_reserve(alloc::resolve(&*self.heap), needed)
+        }
+    }
+
+    /// Insert a native str type at a char index.
+    fn insert_str(&mut self, index: usize, value: &str, op: &str) {
+        let offset = match self.char_to_byte(index) {
+            Some(offset) => offset,
+            None => panic!("shsc::String: {}: index out of bounds for {}", op, index),
+        };
+        if let Err(err) = self.try_reserve(value.len()) {
+            err.abort();
+        }
+        unsafe {
+            (*self.buf).insert(offset, value.as_bytes());
+            *self.len += value.chars().count();
+        }
+    }
 }
 
 impl traits::Append<char> for crate::String {
@@ -108,11 +358,12 @@ impl traits::Append<char> for crate::String {
     /// s.append('o');
     /// s.append('r');
     /// s.append('l');
-    /// s.append('d')
+    /// s.append('d');
+    /// assert_eq!(s.as_str(), "hello world");
     /// ```
 
     fn append(&mut self, c: char) {
-        self.str.append(crate::todata!(c));
+        self.append(c.encode_utf8(&mut [0; 4]) as &str);
     }
 }
 
@@ -127,13 +378,13 @@ impl traits::Append<&crate::String> for crate::String {
     /// let mut s = shsc::String::from("hello");
     /// let s2 = shsc::String::from(" world");
     /// s.append(&s2);
+    /// assert_eq!(s.as_str(), "hello world");
     /// ```
 
     fn append(&mut self, str: &crate::String) {
-        for item in str.str.as_vec() {
-            // clone is acceptable here because data is of type CHAR
-            self.str.append(item.clone());
-        }
+        // the source may be a reference copy of self, so copy it out first
+        let value = str.as_str().to_owned();
+        self.append(value.as_str());
     }
 }
 
@@ -147,12 +398,18 @@ impl traits::Append<&str> for crate::String {
     /// use shsc::traits::Append;
     /// let mut s = shsc::String::from("hello");
     /// s.append(" world");
+    /// assert_eq!(s.as_str(), "hello world");
+    ///
+    /// let mut s = shsc::String::from("caf");
+    /// s.append("é");
+    /// s.append(" crème");
+    /// assert_eq!(s.as_str(), "café crème");
+    /// assert_eq!(s.len(), 10);
     /// ```
 
     fn append(&mut self, value: &str) {
-        for c in value.chars() {
-            self.str.append(crate::todata!(c));
-        }
+        let len = self.len();
+        self.insert_str(len, value, "append");
     }
 }
 
@@ -160,7 +417,7 @@ impl traits::Insert<char> for crate::String {
     /// Implement the Insert trait for the String struct.
     /// This allows us to insert native char types into the String struct.
     /// ### Arguments
-    /// * `index` - The index to insert the value at
+    /// * `index` - The char index to insert the value at
     /// * `value` - A native char type
     /// ### Example
     /// ```
@@ -172,10 +429,11 @@ impl traits::Insert<char> for crate::String {
     /// s.insert(8, 'r');
     /// s.insert(9, 'l');
     /// s.insert(10, 'd');
+    /// assert_eq!(s.as_str(), "hello world");
     /// ```
 
     fn insert(&mut self, index: usize, value: char) {
-        self.str.insert(index, crate::todata!(value));
+        self.insert_str(index, value.encode_utf8(&mut [0; 4]), "insert");
     }
 }
 
@@ -183,7 +441,7 @@ impl traits::Insert<&crate::String> for crate::String {
     /// Implement the Insert trait for the String struct.
     /// This allows us to insert shsc::String into itself.
     /// ### Arguments
-    /// * `index` - The index to insert the value at
+    /// * `index` - The char index to insert the value at
     /// * `str` - A shsc::String struct
     /// ### Example
     /// ```
@@ -191,13 +449,13 @@ impl traits::Insert<&crate::String> for crate::String {
     /// let mut s = shsc::String::from("hello");
     /// let s2 = shsc::String::from(" world");
     /// s.insert(5, &s2);
+    /// assert_eq!(s.as_str(), "hello world");
     /// ```
 
     fn insert(&mut self, index: usize, str: &crate::String) {
-        for item in str.str.as_vec() {
-            // clone is acceptable here because data is of type CHAR
-            self.str.insert(index, item.clone());
-        }
+        // the source may be a reference copy of self, so copy it out first
+        let value = str.as_str().to_owned();
+        self.insert_str(index, &value, "insert");
     }
 }
 
@@ -205,19 +463,18 @@ impl traits::Insert<&str> for crate::String {
     /// Implement the Insert trait for the String struct.
     /// This allows us to insert native str types into the String struct.
     /// ### Arguments
-    /// * `index` - The index to insert the value at
+    /// * `index` - The char index to insert the value at
     /// * `value` - A native str type
     /// ### Example
     /// ```
     /// use shsc::traits::Insert;
-    /// let mut s = shsc::String::from("hello");
-    /// s.insert(5, " world");
+    /// let mut s = shsc::String::from("héllo");
+    /// s.insert(1, "ey, h");
+    /// assert_eq!(s.as_str(), "hey, héllo");
     /// ```
 
     fn insert(&mut self, index: usize, value: &str) {
-        for c in value.chars() {
-            self.str.insert(index, crate::todata!(c));
-        }
+        self.insert_str(index, value, "insert");
     }
 }
 
@@ -235,20 +492,7 @@ impl traits::ToStr for String {
     /// ```
 
     fn tostr(&self) -> std::string::String {
-        let mut str = std::string::String::new();
-        for (index, chr) in self.str.as_vec().iter().enumerate() {
-            if let crate::DataTypes::CHAR(c) = chr.data {
-                str.push(c);
-            } else {
-                panic!(
-                    "shsc::String: expected type {} at index {}, found type {}",
-                    crate::DataTypes::CHAR('\0').typename(),
-                    index,
-                    chr.data.typename()
-                );
-            }
-        }
-        str
+        self.as_str().to_owned()
     }
 }
 
@@ -259,14 +503,20 @@ impl traits::RefCopy for String {
     /// A reference counted copy of the String struct
     /// ### Example
     /// ```
-    /// use shsc::traits::RefCopy;
+    /// use shsc::traits::{Append, RefCopy};
     /// let mut s = shsc::String::from("hello");
-    /// let s2 = s.refcopy();
+    /// let mut s2 = s.refcopy();
+    /// s2.append('!');
+    /// assert_eq!(s.as_str(), "hello!");
     /// ```
 
     fn refcopy(&mut self) -> Self {
+        self.incrc();
         crate::String {
-            str: self.str.refcopy(),
+            buf: self.buf,
+            len: self.len,
+            heap: self.heap,
+            refc: self.refc,
         }
     }
 
@@ -301,9 +551,49 @@ impl Clone for String {
     /// ```
 
     fn clone(&self) -> Self {
-        crate::String {
-            str: self.str.clone(),
-        }
+        crate::String::from(self.as_str())
+    }
+}
+
+impl PartialEq for String {
+    /// Implement the PartialEq trait for the String struct.
+    /// ### Example
+    /// ```
+    /// assert!(shsc::String::from("hello") == shsc::String::from("hello"));
+    /// assert!(shsc::String::from("hello") != shsc::String::from("world"));
+    /// ```
+
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for String {}
+
+impl PartialOrd for String {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for String {
+    /// Implement the Ord trait for the String struct.
+    /// Strings compare lexicographically by character.
+    /// ### Example
+    /// ```
+    /// assert!(shsc::String::from("abc") < shsc::String::from("abd"));
+    /// assert!(shsc::String::from("ab") < shsc::String::from("abc"));
+    /// ```
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        // UTF-8 byte order is code point order
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for String {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
     }
 }
 
@@ -311,34 +601,108 @@ impl traits::RefC for String {
     /// Implement the RefC trait for the String struct.
     /// This allows us to increment the reference count of the String struct.
     fn incrc(&mut self) {
-        self.str.incrc();
+        unsafe { *self.refc += 1 };
     }
 
     /// Implement the RefC trait for the String struct.
     /// This allows us to decrement the reference count of the String struct.
     fn decrc(&mut self) {
-        self.str.decrc();
+        unsafe {
+            *self.refc -= 1;
+            if (*self.refc) < 0 {
+                *self.refc = 0;
+            }
+        }
     }
 
     /// Implement the RefC trait for the String struct.
     /// This allows us to get the reference count of the String struct.
     fn getrc(&self) -> i64 {
-        self.str.getrc()
+        unsafe { *self.refc }
     }
 }
 
 impl Drop for String {
     /// Implement the Drop trait for the String struct.
     /// This allows us to deallocate the String struct when the reference count reaches zero.
-    /// ### Example
-    /// ```
-    /// let s = shsc::String::from("hello");
-    /// ```
-
     fn drop(&mut self) {
         self.decrc();
         if self.getrc() > 0 {
             return;
         }
+        crate::debug::forget(self.addr());
+        unsafe {
+            (*self.buf).release(alloc::resolve(&*self.heap));
+            drop(Box::from_raw(self.buf));
+            drop(Box::from_raw(self.len));
+            drop(Box::from_raw(self.heap));
+            drop(Box::from_raw(self.refc));
+        }
+    }
+}
+
+impl FromIterator<char> for String {
+    /// Collect chars into a new String struct.
+    /// ### Example
+    /// ```
+    /// let s: shsc::String = "hello".chars().rev().collect();
+    /// assert_eq!(s.as_str(), "olleh");
+    /// ```
+
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut str = crate::String::new();
+        str.extend(iter);
+        str
+    }
+}
+
+impl<'a> FromIterator<&'a str> for String {
+    /// Collect native str types into a new String struct.
+    /// ### Example
+    /// ```
+    /// let s: shsc::String = vec!["hello", " ", "world"].into_iter().collect();
+    /// assert_eq!(s.as_str(), "hello world");
+    /// ```
+
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut str = crate::String::new();
+        str.extend(iter);
+        str
+    }
+}
+
+impl Extend<char> for String {
+    /// Append every char of an iterator to the String struct.
+    /// ### Example
+    /// ```
+    /// let mut s = shsc::String::from("ab");
+    /// s.extend(vec!['c', 'd']);
+    /// assert_eq!(s.as_str(), "abcd");
+    /// ```
+
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        if let Err(err) = self.try_reserve(iter.size_hint().0) {
+            err.abort();
+        }
+        for c in iter {
+            self.append(c);
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for String {
+    /// Append every native str type of an iterator to the String struct.
+    /// ### Example
+    /// ```
+    /// let mut s = shsc::String::from("a");
+    /// s.extend(vec!["b", "c"]);
+    /// assert_eq!(s.as_str(), "abc");
+    /// ```
+
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for value in iter {
+            self.append(value);
+        }
     }
 }
@@ -0,0 +1,344 @@
+//! Interned strings used as shsc::Map keys.
+//!
+//! Each thread keeps a table of the keys it has seen, and maps hold a
+//! shsc::Symbol handle to the shared text instead of their own copy. Symbols
+//! are reference counted: text is freed once no map or handle uses it, and
+//! the table drops its entries for unused text as it grows and when the
+//! thread exits. Interning takes no lock, and neither does looking up a map
+//! entry by a native str.
+//!
+//! Symbols compare by their text, so a key interned on one thread finds the
+//! same entry as the key interned on another. A symbol carries the hash of
+//! its text, so looking up a map entry by a symbol does not hash the text,
+//! and symbols for the same text from one thread share an address, so the
+//! key is compared without reading it either.
+//!
+//! ### Example
+//! ```
+//! let mut a = shsc::Map::new();
+//! let mut b = shsc::Map::new();
+//! a.insert("name", shsc::todata!(1));
+//! b.insert("name", shsc::todata!(2));
+//! let name = shsc::Symbol::intern("name");
+//! assert_eq!(a.get(&name).unwrap().as_i64(), Some(1));
+//! assert_eq!(b.get(&name).unwrap().as_i64(), Some(2));
+//! ```
+
+use crate::traits;
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::{Arc, OnceLock};
+
+// seeded once per process, so every thread hashes text the same way
+static SEED: OnceLock<RandomState> = OnceLock::new();
+
+/// Hash text the way a symbol holding it is hashed.
+fn hash_text(text: &str) -> u64 {
+    SEED.get_or_init(RandomState::new).hash_one(text)
+}
+
+/// The symbols interned on one thread.
+struct Table {
+    symbols: HashSet<Symbol>,
+    /// Size at which unused symbols are dropped before adding more
+    limit: usize,
+}
+
+impl Table {
+    const MIN_LIMIT: usize = 64;
+
+    /// Drop the symbols that only the table still holds.
+    fn purge(&mut self) {
+        self.symbols
+            .retain(|symbol| Arc::strong_count(&symbol.text) > 1);
+        self.limit = Table::MIN_LIMIT.max(self.symbols.len() * 2);
+    }
+}
+
+thread_local! {
+    static TABLE: RefCell<Table> = RefCell::new(Table {
+        symbols: HashSet::new(),
+        limit: Table::MIN_LIMIT,
+    });
+}
+
+/// A handle to an interned string.
+/// Cloning a symbol only counts a reference, and equal symbols from the same
+/// thread share the same text.
+#[derive(Clone)]
+pub struct Symbol {
+    text: Arc<str>,
+    hash: u64,
+}
+
+impl Symbol {
+    /// Get the symbol for a string, interning it on this thread if it is new.
+    /// ### Arguments
+    /// * `value` - A native str type
+    /// ### Returns
+    /// The symbol for the string
+    /// ### Example
+    /// ```
+    /// let a = shsc::Symbol::intern("key");
+    /// let b = shsc::Symbol::intern(&"KEY".to_lowercase());
+    /// assert_eq!(a, b);
+    /// assert!(std::ptr::eq(a.as_str(), b.as_str()));
+    /// ```
+
+    pub fn intern(value: &str) -> Symbol {
+        let interned = TABLE.try_with(|table| {
+            let mut table = table.borrow_mut();
+            if let Some(symbol) = table.symbols.get(Text::new(value)) {
+                return symbol.clone();
+            }
+            if table.symbols.len() >= table.limit {
+                table.purge();
+            }
+            let symbol = Symbol::new(value);
+            table.symbols.insert(symbol.clone());
+            symbol
+        });
+        // the table is gone while the thread exits, so the text is not shared
+        interned.unwrap_or_else(|_| Symbol::new(value))
+    }
+
+    /// Make a symbol that is not interned.
+    fn new(value: &str) -> Symbol {
+        Symbol {
+            text: Arc::from(value),
+            hash: hash_text(value),
+        }
+    }
+
+    /// Get the symbol for a string only if it is interned on this thread.
+    /// ### Arguments
+    /// * `value` - A native str type
+    /// ### Returns
+    /// The symbol, or None if the string is not interned
+    /// ### Example
+    /// ```
+    /// let known = shsc::Symbol::intern("known");
+    /// assert!(shsc::Symbol::lookup("known").is_some());
+    /// assert!(shsc::Symbol::lookup("never interned anywhere").is_none());
+    /// ```
+
+    pub fn lookup(value: &str) -> Option<Symbol> {
+        TABLE
+            .try_with(|table| table.borrow().symbols.get(Text::new(value)).cloned())
+            .ok()
+            .flatten()
+    }
+
+    /// Get the text of the symbol.
+    /// ### Example
+    /// ```
+    /// assert_eq!(shsc::Symbol::intern("key").as_str(), "key");
+    /// ```
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Get the number of distinct strings interned on this thread that are still in use.
+/// ### Example
+/// ```
+/// let before = shsc::symbol::interned();
+/// let mut a = shsc::Map::new();
+/// let mut b = shsc::Map::new();
+/// a.insert("a fresh interned key", shsc::todata!(1));
+/// b.insert("a fresh interned key", shsc::todata!(2));
+/// assert_eq!(shsc::symbol::interned(), before + 1);
+/// drop(a);
+/// drop(b);
+/// // the text is freed once no map uses it
+/// assert_eq!(shsc::symbol::interned(), before);
+/// ```
+
+pub fn interned() -> usize {
+    TABLE
+        .try_with(|table| {
+            let mut table = table.borrow_mut();
+            table.purge();
+            table.symbols.len()
+        })
+        .unwrap_or(0)
+}
+
+/// Types that can be used to look up a shsc::Map entry.
+pub trait Key {
+    /// Get the symbol for the key, interning it if it is new.
+    fn intern(&self) -> Symbol;
+
+    /// Get the text of the key.
+    fn text(&self) -> &str;
+
+    /// Get the key as a symbol if it already is one, to look it up without hashing the text.
+    fn symbol(&self) -> Option<&Symbol> {
+        None
+    }
+}
+
+impl Key for Symbol {
+    fn intern(&self) -> Symbol {
+        self.clone()
+    }
+
+    fn text(&self) -> &str {
+        self.as_str()
+    }
+
+    fn symbol(&self) -> Option<&Symbol> {
+        Some(self)
+    }
+}
+
+impl Key for &Symbol {
+    fn intern(&self) -> Symbol {
+        (*self).clone()
+    }
+
+    fn text(&self) -> &str {
+        self.as_str()
+    }
+
+    fn symbol(&self) -> Option<&Symbol> {
+        Some(self)
+    }
+}
+
+impl Key for &str {
+    fn intern(&self) -> Symbol {
+        Symbol::intern(self)
+    }
+
+    fn text(&self) -> &str {
+        self
+    }
+}
+
+impl Key for &std::string::String {
+    fn intern(&self) -> Symbol {
+        Symbol::intern(self)
+    }
+
+    fn text(&self) -> &str {
+        self
+    }
+}
+
+impl Key for std::string::String {
+    fn intern(&self) -> Symbol {
+        Symbol::intern(self)
+    }
+
+    fn text(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq for Symbol {
+    /// Symbols are equal if they have the same text.
+    /// ### Example
+    /// ```
+    /// use shsc::Symbol;
+    /// let here = Symbol::intern("key");
+    /// let there = std::thread::spawn(|| Symbol::intern("key")).join().unwrap();
+    /// assert_eq!(here, there);
+    /// ```
+
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.text, &other.text) || self.text == other.text
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    /// Symbols order by their text, not their address.
+    /// ### Example
+    /// ```
+    /// use shsc::Symbol;
+    /// assert!(Symbol::intern("b") > Symbol::intern("a"));
+    /// ```
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.text.cmp(&other.text)
+    }
+}
+
+impl Hash for Symbol {
+    // the hash of the text was taken when the symbol was made
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+/// The text of a key, hashed the same way as a symbol holding it,
+/// so tables of symbols can be searched by text.
+#[derive(PartialEq, Eq)]
+#[repr(transparent)]
+pub(crate) struct Text(str);
+
+impl Text {
+    pub(crate) fn new(text: &str) -> &Text {
+        // Text is a transparent wrapper around str
+        unsafe { &*(text as *const str as *const Text) }
+    }
+}
+
+impl Hash for Text {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(hash_text(&self.0));
+    }
+}
+
+impl Borrow<Text> for Symbol {
+    fn borrow(&self) -> &Text {
+        Text::new(self.as_str())
+    }
+}
+
+/// A hasher for tables of symbols, which passes on the hash they carry.
+#[derive(Default)]
+pub(crate) struct Prehashed(u64);
+
+impl Hasher for Prehashed {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // symbols and text only write their hash, this keeps other keys usable
+        for &byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(byte);
+        }
+    }
+
+    fn write_u64(&mut self, hash: u64) {
+        self.0 = hash;
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl traits::ToStr for Symbol {
+    fn tostr(&self) -> std::string::String {
+        self.as_str().to_owned()
+    }
+}
@@ -0,0 +1,278 @@
+use crate::traits::{self, RefC};
+use std::sync::atomic::{fence, AtomicI64, Ordering};
+use std::sync::RwLock;
+
+pub struct List {
+    list: *mut RwLock<crate::List>,
+    refc: *mut AtomicI64,
+}
+
+// the contents are only reached through the lock and never shared outside it,
+// and the reference count is atomic
+unsafe impl Send for List {}
+unsafe impl Sync for List {}
+
+impl List {
+    /// Create a new thread-safe list
+    /// ### Returns
+    /// A new list
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::new();
+    /// assert_eq!(list.len(), 0);
+    /// ```
+
+    pub fn new() -> Self {
+        List {
+            list: Box::into_raw(Box::new(RwLock::new(super::detached(
+                crate::List::untracked,
+            )))),
+            refc: Box::into_raw(Box::new(AtomicI64::new(1))),
+        }
+    }
+
+    /// Create a new thread-safe list holding a deep copy of a shsc::List
+    /// ### Arguments
+    /// * `list` - The list to copy
+    /// ### Returns
+    /// A new list, or an error if an item cannot be shared between threads
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::from(&shsc::List::from(vec![shsc::todata!(1)])).unwrap();
+    /// assert_eq!(list.len(), 1);
+    ///
+    /// let f = shsc::todata!(shsc::Function::native(|_args| Ok(shsc::Data::NULL)));
+    /// assert!(shsc::sync::List::from(&shsc::List::from(vec![f])).is_err());
+    /// ```
+
+    pub fn from(list: &crate::List) -> Result<Self, super::ShareError> {
+        let synced = List::new();
+        for item in list.iter() {
+            synced.append(item.clone())?;
+        }
+        Ok(synced)
+    }
+
+    /// Append data to the list
+    /// ### Arguments
+    /// * `data` - Data to append, deep copied into the list
+    /// ### Returns
+    /// An error if the data cannot be shared between threads
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::new();
+    /// list.append(shsc::todata!(1)).unwrap();
+    /// assert_eq!(list.get(0).unwrap().as_i64(), Some(1));
+    ///
+    /// let f = shsc::todata!(shsc::Function::native(|_args| Ok(shsc::Data::NULL)));
+    /// let err = list.append(shsc::todata!(vec![f])).unwrap_err();
+    /// assert_eq!(err.to_string(), "shsc::sync::List: append: FUNCTION cannot be shared between threads");
+    /// assert_eq!(list.len(), 1);
+    /// ```
+
+    pub fn append(&self, data: crate::Data) -> Result<(), super::ShareError> {
+        let data = super::detach(&data, "shsc::sync::List: append")?;
+        self.write().append(data);
+        Ok(())
+    }
+
+    /// Get a copy of the data at the specified index
+    /// ### Arguments
+    /// * `index` - The index of the data
+    /// ### Returns
+    /// A deep copy of the data, or None if the index is out of bounds
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::from(&shsc::List::from(vec![shsc::todata!(1)])).unwrap();
+    /// assert!(list.get(0).is_some());
+    /// assert!(list.get(1).is_none());
+    /// ```
+
+    pub fn get(&self, index: usize) -> Option<crate::Data> {
+        self.read().get(index).cloned()
+    }
+
+    /// Replace the data at the specified index
+    /// ### Arguments
+    /// * `index` - The index of the data
+    /// * `data` - Data to store, deep copied into the list
+    /// ### Returns
+    /// An error if the data cannot be shared between threads
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::from(&shsc::List::from(vec![shsc::todata!(1)])).unwrap();
+    /// list.set(0, shsc::todata!(2)).unwrap();
+    /// assert_eq!(list.get(0).unwrap().as_i64(), Some(2));
+    /// ```
+
+    pub fn set(&self, index: usize, data: crate::Data) -> Result<(), super::ShareError> {
+        let data = super::detach(&data, "shsc::sync::List: set")?;
+        let mut list = self.write();
+        let len = list.len();
+        match list.get_mut(index) {
+            Some(slot) => *slot = data,
+            None => panic!("shsc::sync::List: set: index out of bounds for {}", len),
+        }
+        Ok(())
+    }
+
+    /// Insert data at the specified index
+    /// ### Arguments
+    /// * `index` - The index at which to insert
+    /// * `data` - Data to insert, deep copied into the list
+    /// ### Returns
+    /// An error if the data cannot be shared between threads
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::from(&shsc::List::from(vec![shsc::todata!(1)])).unwrap();
+    /// list.insert(0, shsc::todata!(0)).unwrap();
+    /// assert_eq!(list.get(0).unwrap().as_i64(), Some(0));
+    /// ```
+
+    pub fn insert(&self, index: usize, data: crate::Data) -> Result<(), super::ShareError> {
+        let data = super::detach(&data, "shsc::sync::List: insert")?;
+        self.write().insert(index, data);
+        Ok(())
+    }
+
+    /// Remove the data at the specified index
+    /// ### Arguments
+    /// * `index` - The index of the data
+    /// ### Returns
+    /// The removed data
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::from(&shsc::List::from(vec![shsc::todata!(1)])).unwrap();
+    /// assert_eq!(list.remove(0).as_i64(), Some(1));
+    /// assert_eq!(list.len(), 0);
+    /// ```
+
+    pub fn remove(&self, index: usize) -> crate::Data {
+        self.write().remove(index)
+    }
+
+    /// Get the length of the list
+    /// ### Returns
+    /// The length of the list
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::new();
+    /// assert_eq!(list.len(), 0);
+    /// ```
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Get a deep copy of the contents as a plain shsc::List
+    /// ### Returns
+    /// A new list owned by the calling thread
+    /// ### Example
+    /// ```
+    /// let list = shsc::sync::List::from(&shsc::List::from(vec![shsc::todata!(1)])).unwrap();
+    /// assert_eq!(list.snapshot().len(), 1);
+    /// ```
+
+    pub fn snapshot(&self) -> crate::List {
+        self.read().clone()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, crate::List> {
+        unsafe { &*self.list }
+            .read()
+            .expect("shsc::sync::List: lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, crate::List> {
+        unsafe { &*self.list }
+            .write()
+            .expect("shsc::sync::List: lock poisoned")
+    }
+}
+
+impl traits::ToStr for List {
+    /// Implement the ToStr trait for the sync List struct.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list = shsc::sync::List::from(&shsc::List::from(vec![shsc::todata!(1)])).unwrap();
+    /// assert_eq!(list.tostr(), "[1]");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        self.read().tostr()
+    }
+}
+
+impl traits::RefCopy for List {
+    /// Implement the RefCopy trait for the sync List struct.
+    /// The reference copy can be moved to another thread.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// let mut list = shsc::sync::List::new();
+    /// let copy = list.refcopy();
+    /// std::thread::spawn(move || copy.append(shsc::todata!(1)))
+    ///     .join()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(list.len(), 1);
+    /// ```
+
+    fn refcopy(&mut self) -> Self {
+        self.incrc();
+        List {
+            list: self.list,
+            refc: self.refc,
+        }
+    }
+
+    /// Implement the RefCopy trait for the sync List struct.
+    /// The list is deallocated when the last reference copy is dropped.
+    fn refdrop(self) {
+        // drop trait is called here
+    }
+}
+
+impl Clone for List {
+    /// Implement the Clone trait for the sync List struct.
+    /// Results in a new list with a reference count of 1.
+    fn clone(&self) -> Self {
+        let list = self.read();
+        List {
+            list: Box::into_raw(Box::new(RwLock::new(super::detached(|| list.clone())))),
+            refc: Box::into_raw(Box::new(AtomicI64::new(1))),
+        }
+    }
+}
+
+impl traits::RefC for List {
+    fn incrc(&mut self) {
+        unsafe { (*self.refc).fetch_add(1, Ordering::Relaxed) };
+    }
+
+    fn decrc(&mut self) {
+        let _ = unsafe { &*self.refc }.fetch_update(Ordering::Release, Ordering::Relaxed, |rc| {
+            Some((rc - 1).max(0))
+        });
+    }
+
+    fn getrc(&self) -> i64 {
+        unsafe { (*self.refc).load(Ordering::Acquire) }
+    }
+}
+
+impl Drop for List {
+    /// Implement the Drop trait for the sync List struct.
+    /// The last reference copy to be dropped, on whichever thread, deallocates the list.
+    fn drop(&mut self) {
+        if unsafe { (*self.refc).fetch_sub(1, Ordering::Release) } != 1 {
+            return;
+        }
+        fence(Ordering::Acquire);
+        unsafe {
+            drop(Box::from_raw(self.list));
+            drop(Box::from_raw(self.refc));
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::traits::{self, RefC};
+use std::sync::atomic::{fence, AtomicI64, Ordering};
+use std::sync::RwLock;
+
+pub struct Map {
+    map: *mut RwLock<crate::Map>,
+    refc: *mut AtomicI64,
+}
+
+// the contents are only reached through the lock and never shared outside it,
+// and the reference count is atomic
+unsafe impl Send for Map {}
+unsafe impl Sync for Map {}
+
+impl Map {
+    /// Create a new thread-safe map
+    /// ### Example
+    /// ```
+    /// let m = shsc::sync::Map::new();
+    /// assert_eq!(m.len(), 0);
+    /// ```
+
+    pub fn new() -> Self {
+        Map {
+            map: Box::into_raw(Box::new(RwLock::new(super::detached(crate::Map::new)))),
+            refc: Box::into_raw(Box::new(AtomicI64::new(1))),
+        }
+    }
+
+    /// Create a new thread-safe map holding a deep copy of a shsc::Map
+    /// ### Arguments
+    /// * `map` - The map to copy
+    /// ### Returns
+    /// A new map, or an error if a value cannot be shared between threads
+    /// ### Example
+    /// ```
+    /// let mut m = shsc::Map::new();
+    /// m.insert("key", shsc::todata!(1));
+    /// let synced = shsc::sync::Map::from(&m).unwrap();
+    /// assert_eq!(synced.get("key").unwrap().as_i64(), Some(1));
+    /// ```
+
+    pub fn from(map: &crate::Map) -> Result<Self, super::ShareError> {
+        let synced = Map::new();
+        for (key, value) in map.iter() {
+            synced.insert(key, value.clone())?;
+        }
+        Ok(synced)
+    }
+
+    /// Insert a key-value pair into the map
+    /// ### Arguments
+    /// * `key` - The key
+    /// * `value` - The value, deep copied into the map
+    /// ### Returns
+    /// An error if the value cannot be shared between threads
+    /// ### Example
+    /// ```
+    /// let m = shsc::sync::Map::new();
+    /// m.insert("key", shsc::todata!(1)).unwrap();
+    /// assert_eq!(m.len(), 1);
+    /// ```
+
+    pub fn insert(&self, key: &str, value: crate::Data) -> Result<(), super::ShareError> {
+        let value = super::detach(&value, "shsc::sync::Map: insert")?;
+        self.write().insert(key, value);
+        Ok(())
+    }
+
+    /// Get a copy of the value for a key
+    /// ### Arguments
+    /// * `key` - The key
+    /// ### Returns
+    /// A deep copy of the value, or None if the key is not present
+    /// ### Example
+    /// ```
+    /// let m = shsc::sync::Map::new();
+    /// m.insert("key", shsc::todata!(1)).unwrap();
+    /// assert!(m.get("key").is_some());
+    /// assert!(m.get("other").is_none());
+    /// ```
+
+    pub fn get(&self, key: &str) -> Option<crate::Data> {
+        self.read().get(key).cloned()
+    }
+
+    /// Remove a key-value pair from the map
+    /// ### Arguments
+    /// * `key` - The key
+    /// ### Returns
+    /// The removed value, or None if the key is not present
+    /// ### Example
+    /// ```
+    /// let m = shsc::sync::Map::new();
+    /// m.insert("key", shsc::todata!(1)).unwrap();
+    /// assert_eq!(m.remove("key").unwrap().as_i64(), Some(1));
+    /// assert!(m.remove("key").is_none());
+    /// ```
+
+    pub fn remove(&self, key: &str) -> Option<crate::Data> {
+        self.write().remove(key)
+    }
+
+    /// Get the number of key-value pairs in the map
+    /// ### Example
+    /// ```
+    /// let m = shsc::sync::Map::new();
+    /// assert_eq!(m.len(), 0);
+    /// ```
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Get the keys of the map
+    /// ### Returns
+    /// The keys in no particular order
+    /// ### Example
+    /// ```
+    /// let m = shsc::sync::Map::new();
+    /// m.insert("key", shsc::todata!(1)).unwrap();
+    /// assert_eq!(m.keys(), vec!["key".to_string()]);
+    /// ```
+
+    pub fn keys(&self) -> Vec<std::string::String> {
+        self.read().keys().map(str::to_owned).collect()
+    }
+
+    /// Get a deep copy of the contents as a plain shsc::Map
+    /// ### Returns
+    /// A new map owned by the calling thread
+    /// ### Example
+    /// ```
+    /// let m = shsc::sync::Map::new();
+    /// m.insert("key", shsc::todata!(1)).unwrap();
+    /// assert!(m.snapshot().get("key").is_some());
+    /// ```
+
+    pub fn snapshot(&self) -> crate::Map {
+        self.read().clone()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, crate::Map> {
+        unsafe { &*self.map }
+            .read()
+            .expect("shsc::sync::Map: lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, crate::Map> {
+        unsafe { &*self.map }
+            .write()
+            .expect("shsc::sync::Map: lock poisoned")
+    }
+}
+
+impl traits::ToStr for Map {
+    /// Implement the ToStr trait for the sync Map struct.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let m = shsc::sync::Map::new();
+    /// m.insert("key", shsc::todata!(1)).unwrap();
+    /// assert_eq!(m.tostr(), "{key: 1}");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        self.read().tostr()
+    }
+}
+
+impl traits::RefCopy for Map {
+    /// Implement the RefCopy trait for the sync Map struct.
+    /// The reference copy can be moved to another thread.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// let mut m = shsc::sync::Map::new();
+    /// let copy = m.refcopy();
+    /// std::thread::spawn(move || copy.insert("key", shsc::todata!(1)))
+    ///     .join()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(m.len(), 1);
+    /// ```
+
+    fn refcopy(&mut self) -> Self {
+        self.incrc();
+        Map {
+            map: self.map,
+            refc: self.refc,
+        }
+    }
+
+    /// Implement the RefCopy trait for the sync Map struct.
+    /// The map is deallocated when the last reference copy is dropped.
+    fn refdrop(self) {
+        // drop trait is called here
+    }
+}
+
+impl Clone for Map {
+    /// Implement the Clone trait for the sync Map struct.
+    /// Results in a new map with a reference count of 1.
+    fn clone(&self) -> Self {
+        let map = self.read();
+        Map {
+            map: Box::into_raw(Box::new(RwLock::new(super::detached(|| map.clone())))),
+            refc: Box::into_raw(Box::new(AtomicI64::new(1))),
+        }
+    }
+}
+
+impl traits::RefC for Map {
+    fn incrc(&mut self) {
+        unsafe { (*self.refc).fetch_add(1, Ordering::Relaxed) };
+    }
+
+    fn decrc(&mut self) {
+        let _ = unsafe { &*self.refc }.fetch_update(Ordering::Release, Ordering::Relaxed, |rc| {
+            Some((rc - 1).max(0))
+        });
+    }
+
+    fn getrc(&self) -> i64 {
+        unsafe { (*self.refc).load(Ordering::Acquire) }
+    }
+}
+
+impl Drop for Map {
+    /// Implement the Drop trait for the sync Map struct.
+    /// The last reference copy to be dropped, on whichever thread, deallocates the map.
+    fn drop(&mut self) {
+        if unsafe { (*self.refc).fetch_sub(1, Ordering::Release) } != 1 {
+            return;
+        }
+        fence(Ordering::Acquire);
+        unsafe {
+            drop(Box::from_raw(self.map));
+            drop(Box::from_raw(self.refc));
+        }
+    }
+}
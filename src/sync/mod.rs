@@ -0,0 +1,84 @@
+//! Thread-safe counterparts of shsc::List, shsc::Map and shsc::String.
+//!
+//! The plain containers share their contents through non-atomic reference counts,
+//! so they cannot leave the thread that created them. The types here keep their
+//! contents behind a `RwLock` and count references atomically, so reference
+//! copies can be moved to and used from other threads.
+//!
+//! Values are deep copied on the way in and on the way out, so no reference into
+//! the locked contents ever escapes. FUNCTION, USERDATA and WEAK values are tied
+//! to the thread that created them and are rejected with a ShareError.
+//!
+//! ### Example
+//! ```
+//! use shsc::traits::RefCopy;
+//! let mut config = shsc::sync::Map::new();
+//! config.insert("workers", shsc::todata!(4)).unwrap();
+//! let handles: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         let config = config.refcopy();
+//!         std::thread::spawn(move || config.get("workers").unwrap().as_i64())
+//!     })
+//!     .collect();
+//! for handle in handles {
+//!     assert_eq!(handle.join().unwrap(), Some(4));
+//! }
+//! ```
+
+pub mod list;
+pub mod map;
+pub mod string;
+
+use std::fmt;
+
+pub use list::List;
+pub use map::Map;
+pub use string::String;
+
+/// Error returned when a value cannot be stored in a thread-safe container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareError {
+    /// The value is or holds data tied to its thread; holds the operation and that typename.
+    NotShareable(&'static str, std::string::String),
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::NotShareable(context, typename) => {
+                write!(
+                    f,
+                    "{}: {} cannot be shared between threads",
+                    context, typename
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// Deep copy a value for storage in a thread-safe container.
+fn detach(data: &crate::Data, context: &'static str) -> Result<crate::Data, ShareError> {
+    check(data, context)?;
+    Ok(detached(|| data.clone()))
+}
+
+/// Run f so that the containers it creates can be dropped on another thread.
+/// They are never registered with the cycle collector and their memory comes
+/// from the global allocator.
+fn detached<R>(f: impl FnOnce() -> R) -> R {
+    crate::gc::suspended(|| crate::util::alloc::with_global(f))
+}
+
+/// Fail if the value holds anything that cannot be shared between threads.
+fn check(data: &crate::Data, context: &'static str) -> Result<(), ShareError> {
+    match &data.data {
+        crate::DataTypes::FUNCTION(_)
+        | crate::DataTypes::USERDATA(_)
+        | crate::DataTypes::WEAK(_) => Err(ShareError::NotShareable(context, data.typename())),
+        crate::DataTypes::LIST(list) => list.iter().try_for_each(|item| check(item, context)),
+        crate::DataTypes::MAP(map) => map.values().try_for_each(|value| check(value, context)),
+        _ => Ok(()),
+    }
+}
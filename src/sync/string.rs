@@ -0,0 +1,172 @@
+use crate::traits::{self, RefC};
+use std::sync::atomic::{fence, AtomicI64, Ordering};
+use std::sync::RwLock;
+
+pub struct String {
+    str: *mut RwLock<crate::String>,
+    refc: *mut AtomicI64,
+}
+
+// the contents are only reached through the lock and never shared outside it,
+// and the reference count is atomic
+unsafe impl Send for String {}
+unsafe impl Sync for String {}
+
+impl String {
+    /// Create a new thread-safe string
+    /// ### Example
+    /// ```
+    /// let s = shsc::sync::String::new();
+    /// assert_eq!(s.len(), 0);
+    /// ```
+
+    pub fn new() -> Self {
+        String::wrap(super::detached(crate::String::new))
+    }
+
+    /// Create a new thread-safe string from a native str type
+    /// ### Arguments
+    /// * `value` - A native str type
+    /// ### Returns
+    /// A new String struct
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let s = shsc::sync::String::from("hello");
+    /// assert_eq!(s.tostr(), "hello");
+    /// ```
+
+    pub fn from(value: &str) -> Self {
+        String::wrap(super::detached(|| crate::String::from(value)))
+    }
+
+    /// Get the length of the string in chars
+    /// ### Example
+    /// ```
+    /// let s = shsc::sync::String::from("hello");
+    /// assert_eq!(s.len(), 5);
+    /// ```
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Append to the string, taking the lock for the duration of the call
+    /// ### Arguments
+    /// * `value` - A native char or str type
+    /// ### Example
+    /// ```
+    /// use shsc::traits::{RefCopy, ToStr};
+    /// let mut s = shsc::sync::String::from("hello");
+    /// let copy = s.refcopy();
+    /// std::thread::spawn(move || copy.append(" world"))
+    ///     .join()
+    ///     .unwrap();
+    /// s.append('!');
+    /// assert_eq!(s.tostr(), "hello world!");
+    /// ```
+
+    pub fn append<T>(&self, value: T)
+    where
+        crate::String: traits::Append<T>,
+    {
+        traits::Append::append(&mut *self.write(), value);
+    }
+
+    /// Get a deep copy of the contents as a plain shsc::String
+    /// ### Returns
+    /// A new string owned by the calling thread
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let s = shsc::sync::String::from("hello");
+    /// assert_eq!(s.snapshot().tostr(), "hello");
+    /// ```
+
+    pub fn snapshot(&self) -> crate::String {
+        self.read().clone()
+    }
+
+    fn wrap(str: crate::String) -> Self {
+        String {
+            str: Box::into_raw(Box::new(RwLock::new(str))),
+            refc: Box::into_raw(Box::new(AtomicI64::new(1))),
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, crate::String> {
+        unsafe { &*self.str }
+            .read()
+            .expect("shsc::sync::String: lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, crate::String> {
+        unsafe { &*self.str }
+            .write()
+            .expect("shsc::sync::String: lock poisoned")
+    }
+}
+
+impl traits::ToStr for String {
+    /// Implement the ToStr trait for the sync String struct.
+    fn tostr(&self) -> std::string::String {
+        self.read().tostr()
+    }
+}
+
+impl traits::RefCopy for String {
+    /// Implement the RefCopy trait for the sync String struct.
+    /// The reference copy can be moved to another thread.
+    fn refcopy(&mut self) -> Self {
+        self.incrc();
+        String {
+            str: self.str,
+            refc: self.refc,
+        }
+    }
+
+    /// Implement the RefCopy trait for the sync String struct.
+    /// The string is deallocated when the last reference copy is dropped.
+    fn refdrop(self) {
+        // drop trait is called here
+    }
+}
+
+impl Clone for String {
+    /// Implement the Clone trait for the sync String struct.
+    /// Results in a new string with a reference count of 1.
+    fn clone(&self) -> Self {
+        String::wrap(super::detached(|| self.read().clone()))
+    }
+}
+
+impl traits::RefC for String {
+    fn incrc(&mut self) {
+        unsafe { (*self.refc).fetch_add(1, Ordering::Relaxed) };
+    }
+
+    fn decrc(&mut self) {
+        let _ = unsafe { &*self.refc }.fetch_update(Ordering::Release, Ordering::Relaxed, |rc| {
+            Some((rc - 1).max(0))
+        });
+    }
+
+    fn getrc(&self) -> i64 {
+        unsafe { (*self.refc).load(Ordering::Acquire) }
+    }
+}
+
+impl Drop for String {
+    /// Implement the Drop trait for the sync String struct.
+    /// The last reference copy to be dropped, on whichever thread, deallocates the string.
+    fn drop(&mut self) {
+        if unsafe { (*self.refc).fetch_sub(1, Ordering::Release) } != 1 {
+            return;
+        }
+        fence(Ordering::Acquire);
+        unsafe {
+            drop(Box::from_raw(self.str));
+            drop(Box::from_raw(self.refc));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::traits::{self, RefC};
+use std::any::Any;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+type ToStrFn = dyn Fn(&dyn Any) -> std::string::String;
+
+struct Host {
+    value: Box<dyn Any>,
+    name: std::string::String,
+    tostr: Option<Box<ToStrFn>>,
+}
+
+pub struct UserData {
+    host: *mut Host,
+    refc: *mut i64,
+}
+
+impl UserData {
+    /// Create a new shsc::UserData struct owning a host object.
+    /// ### Arguments
+    /// * `value` - The host object
+    /// * `name` - The name of the host type, reported by name()
+    /// ### Returns
+    /// A new UserData struct
+    /// ### Example
+    /// ```
+    /// let data = shsc::todata!(shsc::UserData::new(std::time::Duration::from_secs(1), "DURATION"));
+    /// assert_eq!(data.typename(), "USERDATA");
+    /// assert_eq!(data.as_userdata().unwrap().name(), "DURATION");
+    /// ```
+
+    pub fn new<T: Any>(value: T, name: &str) -> Self {
+        UserData::from_host(Host {
+            value: Box::new(value),
+            name: name.to_owned(),
+            tostr: None,
+        })
+    }
+
+    /// Create a new shsc::UserData struct owning a host object with a custom string form.
+    /// ### Arguments
+    /// * `value` - The host object
+    /// * `name` - The name of the host type, reported by name()
+    /// * `tostr` - Function used by ToStr
+    /// ### Returns
+    /// A new UserData struct
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let u = shsc::UserData::with_tostr(42u16, "PORT", |port| format!("port {}", port));
+    /// assert_eq!(u.tostr(), "port 42");
+    /// ```
+
+    pub fn with_tostr<T, F>(value: T, name: &str, tostr: F) -> Self
+    where
+        T: Any,
+        F: Fn(&T) -> std::string::String + 'static,
+    {
+        UserData::from_host(Host {
+            value: Box::new(value),
+            name: name.to_owned(),
+            tostr: Some(Box::new(move |value: &dyn Any| {
+                tostr(
+                    value
+                        .downcast_ref::<T>()
+                        .expect("shsc::UserData: tostr: host type changed"),
+                )
+            })),
+        })
+    }
+
+    /// Get another reference to the host object, which cannot be copied.
+    /// Used where only a shared reference is at hand, such as Data::clone.
+    pub(crate) fn share(&self) -> Self {
+        unsafe { *self.refc += 1 };
+        UserData {
+            host: self.host,
+            refc: self.refc,
+        }
+    }
+
+    fn from_host(host: Host) -> Self {
+        UserData {
+            host: Box::into_raw(Box::new(host)),
+            refc: Box::into_raw(Box::new(1)),
+        }
+    }
+
+    /// Get the name of the host type given at creation.
+    /// ### Returns
+    /// The host type name
+    /// ### Example
+    /// ```
+    /// let u = shsc::UserData::new(1u8, "BYTE");
+    /// assert_eq!(u.name(), "BYTE");
+    /// ```
+
+    pub fn name(&self) -> &str {
+        unsafe { &(*self.host).name }
+    }
+
+    /// Check whether the host object is of type T.
+    /// ### Example
+    /// ```
+    /// let u = shsc::UserData::new(1u8, "BYTE");
+    /// assert!(u.is::<u8>());
+    /// assert!(!u.is::<i8>());
+    /// ```
+
+    pub fn is<T: Any>(&self) -> bool {
+        unsafe { (*self.host).value.is::<T>() }
+    }
+
+    /// Get a reference to the host object if it is of type T.
+    /// ### Returns
+    /// A reference to the host object, or None if it has another type
+    /// ### Example
+    /// ```
+    /// let u = shsc::UserData::new(vec![1, 2], "VEC");
+    /// assert_eq!(u.downcast_ref::<Vec<i32>>(), Some(&vec![1, 2]));
+    /// assert!(u.downcast_ref::<String>().is_none());
+    /// ```
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        unsafe { (*self.host).value.downcast_ref::<T>() }
+    }
+
+    /// Get a mutable reference to the host object if it is of type T.
+    /// Changes are seen by every reference copy.
+    /// ### Returns
+    /// A mutable reference to the host object, or None if it has another type
+    /// ### Example
+    /// ```
+    /// let mut u = shsc::UserData::new(vec![1, 2], "VEC");
+    /// u.downcast_mut::<Vec<i32>>().unwrap().push(3);
+    /// assert_eq!(u.downcast_ref::<Vec<i32>>().unwrap().len(), 3);
+    /// ```
+
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        unsafe { (*self.host).value.downcast_mut::<T>() }
+    }
+}
+
+impl traits::ToStr for UserData {
+    /// Implement the ToStr trait for the UserData struct.
+    /// Uses the function given to with_tostr, or `<NAME>` otherwise.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// assert_eq!(shsc::UserData::new((), "SOCKET").tostr(), "<SOCKET>");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        unsafe {
+            let host = &*self.host;
+            match &host.tostr {
+                Some(tostr) => tostr(host.value.as_ref()),
+                None => format!("<{}>", host.name),
+            }
+        }
+    }
+}
+
+impl traits::RefCopy for UserData {
+    /// Implement the RefCopy trait for the UserData struct.
+    /// This allows us to create a reference copy of the UserData struct.
+    fn refcopy(&mut self) -> Self {
+        self.incrc();
+        UserData {
+            host: self.host,
+            refc: self.refc,
+        }
+    }
+
+    /// Implement the RefCopy trait for the UserData struct.
+    /// The host object is dropped when the reference count reaches zero.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::RefCopy;
+    /// struct Handle(std::rc::Rc<std::cell::Cell<bool>>);
+    /// impl Drop for Handle {
+    ///     fn drop(&mut self) {
+    ///         self.0.set(true);
+    ///     }
+    /// }
+    /// let closed = std::rc::Rc::new(std::cell::Cell::new(false));
+    /// let mut u = shsc::UserData::new(Handle(closed.clone()), "HANDLE");
+    /// let u2 = u.refcopy();
+    /// u.refdrop();
+    /// assert!(!closed.get());
+    /// u2.refdrop();
+    /// assert!(closed.get());
+    /// ```
+
+    fn refdrop(self) {
+        // drop trait is called here
+    }
+}
+
+impl PartialEq for UserData {
+    /// Implement the PartialEq trait for the UserData struct.
+    /// User data is equal only to references to the same host object.
+    fn eq(&self, other: &Self) -> bool {
+        self.host == other.host
+    }
+}
+
+impl Eq for UserData {}
+
+impl PartialOrd for UserData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for UserData {
+    /// Implement the Ord trait for the UserData struct.
+    /// User data is ordered by address, which is stable for the life of the host object.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.host as usize).cmp(&(other.host as usize))
+    }
+}
+
+impl Hash for UserData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.host as usize).hash(state);
+    }
+}
+
+impl traits::RefC for UserData {
+    fn incrc(&mut self) {
+        unsafe { *self.refc += 1 };
+    }
+
+    fn decrc(&mut self) {
+        unsafe {
+            *self.refc -= 1;
+            if (*self.refc) < 0 {
+                *self.refc = 0;
+            }
+        }
+    }
+
+    fn getrc(&self) -> i64 {
+        unsafe { *self.refc }
+    }
+}
+
+impl Drop for UserData {
+    /// Implement the Drop trait for the UserData struct.
+    /// Runs the host object's Drop when the reference count reaches zero.
+    fn drop(&mut self) {
+        self.decrc();
+        if self.getrc() > 0 {
+            return;
+        }
+        unsafe {
+            drop(Box::from_raw(self.host));
+            drop(Box::from_raw(self.refc));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::{Allocator, Global};
+use std::alloc::Layout;
+use std::cell::{Cell, RefCell};
+
+const DEFAULT_CHUNK: usize = 64 * 1024;
+const CHUNK_ALIGN: usize = 16;
+
+/// Bump allocator.
+/// Memory is carved out of large chunks and never reused, deallocate does nothing.
+/// All chunks are released together when the arena is dropped, which cannot
+/// happen before every list created with it is gone.
+pub struct Arena {
+    chunks: RefCell<Vec<(*mut u8, Layout)>>,
+    // free space in the current chunk
+    next: Cell<usize>,
+    end: Cell<usize>,
+    // start of the most recent allocation, which can grow in place
+    last: Cell<usize>,
+    chunk_size: usize,
+    allocated: Cell<usize>,
+}
+
+impl Arena {
+    /// Create a new arena with 64 KiB chunks.
+    /// ### Example
+    /// ```
+    /// let arena = shsc::util::alloc::Arena::new();
+    /// assert_eq!(arena.allocated(), 0);
+    /// ```
+
+    pub fn new() -> Self {
+        Arena::with_chunk_size(DEFAULT_CHUNK)
+    }
+
+    /// Create a new arena with chunks of the given size.
+    /// Larger allocations get a chunk of their own.
+    /// ### Arguments
+    /// * `chunk_size` - Size of each chunk in bytes
+    /// ### Example
+    /// ```
+    /// let arena = shsc::util::alloc::Arena::with_chunk_size(4096);
+    /// ```
+
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Arena {
+            chunks: RefCell::new(Vec::new()),
+            next: Cell::new(0),
+            end: Cell::new(0),
+            last: Cell::new(0),
+            chunk_size,
+            allocated: Cell::new(0),
+        }
+    }
+
+    /// Get the number of bytes handed out so far.
+    /// ### Example
+    /// ```
+    /// use shsc::util::alloc::{self, Arena};
+    /// let arena = Arena::new();
+    /// let ptr = alloc::allocate_in::<u64>(&arena, 4);
+    /// assert_eq!(arena.allocated(), 32);
+    /// ```
+
+    pub fn allocated(&self) -> usize {
+        self.allocated.get()
+    }
+
+    fn fits(&self, start: usize, size: usize) -> bool {
+        start
+            .checked_add(size)
+            .is_some_and(|end| end <= self.end.get())
+    }
+}
+
+unsafe impl Allocator for Arena {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        let mut start = self.next.get().next_multiple_of(layout.align());
+        if self.next.get() == 0 || !self.fits(start, layout.size()) {
+            let size = self.chunk_size.max(layout.size() + layout.align());
+            let chunk_layout = match Layout::from_size_align(size, CHUNK_ALIGN) {
+                Ok(chunk_layout) => chunk_layout,
+                Err(_) => return std::ptr::null_mut(),
+            };
+            let chunk = Global.allocate(chunk_layout);
+            if chunk.is_null() {
+                return chunk;
+            }
+            self.chunks.borrow_mut().push((chunk, chunk_layout));
+            self.end.set(chunk as usize + size);
+            start = (chunk as usize).next_multiple_of(layout.align());
+        }
+        self.next.set(start + layout.size());
+        self.last.set(start);
+        self.allocated.set(self.allocated.get() + layout.size());
+        // keep the chunk's provenance rather than casting the address back
+        let chunks = self.chunks.borrow();
+        let (chunk, _) = chunks[chunks.len() - 1];
+        chunk.add(start - chunk as usize)
+    }
+
+    unsafe fn deallocate(&self, _ptr: *mut u8, _layout: Layout) {
+        // released when the arena is dropped
+    }
+
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, newsz: usize) -> *mut u8 {
+        if newsz <= layout.size() {
+            return ptr;
+        }
+        // the most recent allocation can grow into the rest of its chunk
+        if ptr as usize == self.last.get() && self.fits(ptr as usize, newsz) {
+            self.next.set(ptr as usize + newsz);
+            self.allocated
+                .set(self.allocated.get() + newsz - layout.size());
+            return ptr;
+        }
+        let new_ptr = self.allocate(Layout::from_size_align_unchecked(newsz, layout.align()));
+        if !new_ptr.is_null() {
+            std::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size());
+        }
+        new_ptr
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        for &(chunk, layout) in self.chunks.borrow().iter() {
+            unsafe { Global.deallocate(chunk, layout) };
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use super::{Allocator, Global};
+use std::alloc::Layout;
+use std::cell::Cell;
+
+/// Allocator wrapper that counts bytes and enforces an optional ceiling.
+/// Counts are of the bytes requested, the wrapped allocator may hold more.
+/// They include the memory charged by maps for their key index and key text.
+/// Requests that would take the live byte count over the limit fail, which
+/// the try_ methods of the containers report as shsc::util::alloc::AllocError.
+pub struct Metered<A: Allocator = Global> {
+    inner: A,
+    limit: Cell<Option<usize>>,
+    used: Cell<usize>,
+    peak: Cell<usize>,
+    allocations: Cell<usize>,
+    failures: Cell<usize>,
+}
+
+impl Metered<Global> {
+    /// Create a metered global allocator without a limit.
+    /// ### Example
+    /// ```
+    /// let heap = shsc::util::alloc::Metered::new();
+    /// assert_eq!(heap.used(), 0);
+    /// assert_eq!(heap.limit(), None);
+    /// ```
+
+    pub fn new() -> Self {
+        Metered::wrap(Global)
+    }
+}
+
+impl<A: Allocator> Metered<A> {
+    /// Meter another allocator.
+    /// ### Arguments
+    /// * `inner` - The allocator that provides the memory
+    /// ### Example
+    /// ```
+    /// use shsc::util::alloc::{Arena, Metered};
+    /// let heap = Metered::wrap(Arena::new());
+    /// ```
+
+    pub fn wrap(inner: A) -> Self {
+        Metered {
+            inner,
+            limit: Cell::new(None),
+            used: Cell::new(0),
+            peak: Cell::new(0),
+            allocations: Cell::new(0),
+            failures: Cell::new(0),
+        }
+    }
+
+    /// Set the ceiling on live bytes.
+    /// Lowering it below the current use only affects later requests.
+    /// ### Arguments
+    /// * `limit` - The maximum number of live bytes, or None for no limit
+    /// ### Example
+    /// ```
+    /// let heap = shsc::util::alloc::Metered::new();
+    /// heap.set_limit(Some(1 << 20));
+    /// assert_eq!(heap.limit(), Some(1 << 20));
+    /// ```
+
+    pub fn set_limit(&self, limit: Option<usize>) {
+        self.limit.set(limit);
+    }
+
+    /// Get the ceiling on live bytes.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit.get()
+    }
+
+    /// Get the number of live bytes.
+    /// ### Example
+    /// ```
+    /// use std::rc::Rc;
+    /// use shsc::util::alloc::{self, Metered};
+    /// let heap = Rc::new(Metered::new());
+    /// let list = alloc::with_allocator(heap.clone(), || {
+    ///     shsc::List::from(vec![shsc::todata!(1)])
+    /// });
+    /// assert!(heap.used() > 0);
+    /// drop(list);
+    /// assert_eq!(heap.used(), 0);
+    /// ```
+
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    /// Get the highest number of live bytes seen.
+    pub fn peak(&self) -> usize {
+        self.peak.get()
+    }
+
+    /// Get the number of successful allocations.
+    pub fn allocations(&self) -> usize {
+        self.allocations.get()
+    }
+
+    /// Get the number of requests refused because of the limit.
+    pub fn failures(&self) -> usize {
+        self.failures.get()
+    }
+
+    fn admit(&self, extra: usize) -> bool {
+        let allowed = match self.limit.get() {
+            Some(limit) => self
+                .used
+                .get()
+                .checked_add(extra)
+                .is_some_and(|used| used <= limit),
+            None => true,
+        };
+        if !allowed {
+            self.failures.set(self.failures.get() + 1);
+        }
+        allowed
+    }
+
+    fn account(&self, freed: usize, taken: usize) {
+        let used = self.used.get() - freed + taken;
+        self.used.set(used);
+        self.peak.set(self.peak.get().max(used));
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Metered<A> {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        if !self.admit(layout.size()) {
+            return std::ptr::null_mut();
+        }
+        let ptr = self.inner.allocate(layout);
+        if !ptr.is_null() {
+            self.account(0, layout.size());
+            self.allocations.set(self.allocations.get() + 1);
+        }
+        ptr
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.deallocate(ptr, layout);
+        self.account(layout.size(), 0);
+    }
+
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, newsz: usize) -> *mut u8 {
+        if newsz > layout.size() && !self.admit(newsz - layout.size()) {
+            return std::ptr::null_mut();
+        }
+        let new_ptr = self.inner.reallocate(ptr, layout, newsz);
+        if !new_ptr.is_null() {
+            self.account(layout.size(), newsz);
+            self.allocations.set(self.allocations.get() + 1);
+        }
+        new_ptr
+    }
+
+    fn charge(&self, size: usize) -> bool {
+        if !self.admit(size) || !self.inner.charge(size) {
+            return false;
+        }
+        self.account(0, size);
+        true
+    }
+
+    fn discharge(&self, size: usize) {
+        self.inner.discharge(size);
+        self.account(size, 0);
+    }
+}
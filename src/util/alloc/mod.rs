@@ -0,0 +1,266 @@
+//! Memory for List rows and the row table.
+//!
+//! Every allocation goes through an [`Allocator`]. Lists use the allocator that
+//! is current on their thread when they are created, which is [`Global`] unless
+//! changed with [`with_allocator`], and keep it alive until they are deallocated.
+//!
+//! Besides the global allocator two implementations are provided:
+//! * [`Arena`] hands out memory by bumping a pointer and frees it all at once
+//!   when the arena and every list using it are gone
+//! * [`Pool`] keeps freed blocks in power of two size classes for reuse
+//!
+//! [`Metered`] wraps any of them to count bytes and cap the live total. When an
+//! allocation fails the plain container methods abort like the standard
+//! collections do, while their try_ counterparts return [`AllocError`].
+//!
+//! A map keeps its key index on the global heap and charges the bytes it holds
+//! to its allocator through [`Allocator::charge`], so they count against a
+//! [`Metered`] limit too. Only the small fixed size boxes that hold the
+//! reference counts of each container are not counted.
+//!
+//! ### Example
+//! ```
+//! use std::rc::Rc;
+//! use shsc::util::alloc::{self, Arena};
+//! let arena = Rc::new(Arena::new());
+//! let list = alloc::with_allocator(arena.clone(), || {
+//!     shsc::List::from(vec![shsc::todata!(1), shsc::todata!(2)])
+//! });
+//! assert_eq!(list.len(), 2);
+//! assert!(arena.allocated() > 0);
+//! ```
+
+pub mod arena;
+pub mod metered;
+pub mod pool;
+
+pub use arena::Arena;
+pub use metered::Metered;
+pub use pool::Pool;
+
+use std::alloc::{self as stdalloc, Layout};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// Source of raw memory for shsc containers.
+/// Mirrors `std::alloc::GlobalAlloc`: allocation failure is reported by returning null.
+/// ### Safety
+/// Memory returned by `allocate` and `reallocate` must be valid for the requested
+/// layout until it is passed to `deallocate` or `reallocate`, or the allocator is dropped.
+
+pub unsafe trait Allocator {
+    /// Allocate memory for a layout of non-zero size.
+    /// ### Safety
+    /// `layout` must have a non-zero size.
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8;
+
+    /// Release memory obtained from this allocator.
+    /// ### Safety
+    /// `ptr` must have been allocated by this allocator with `layout`.
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout);
+
+    /// Resize memory obtained from this allocator, keeping its contents.
+    /// The default allocates a new block, copies and releases the old one.
+    /// ### Safety
+    /// `ptr` must have been allocated by this allocator with `layout`,
+    /// and `newsz` must be non-zero.
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, newsz: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(newsz, layout.align());
+        let new_ptr = self.allocate(new_layout);
+        if !new_ptr.is_null() {
+            std::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(newsz));
+            self.deallocate(ptr, layout);
+        }
+        new_ptr
+    }
+
+    /// Count memory that containers using this allocator hold outside of it,
+    /// such as the key index of a shsc::Map.
+    /// The default accepts everything without counting it.
+    /// ### Returns
+    /// false to refuse the memory, which the caller then gives up
+    fn charge(&self, size: usize) -> bool {
+        let _ = size;
+        true
+    }
+
+    /// Stop counting memory accepted by charge.
+    fn discharge(&self, size: usize) {
+        let _ = size;
+    }
+}
+
+/// Error returned when an allocator cannot satisfy a request,
+/// either because memory ran out or because a limit was reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocError {
+    layout: Layout,
+}
+
+impl AllocError {
+    /// An error for a request of size bytes.
+    pub(crate) fn of_size(size: usize) -> Self {
+        AllocError {
+            layout: Layout::from_size_align(size, 1).unwrap_or(Layout::new::<u8>()),
+        }
+    }
+
+    /// Get the size in bytes of the failed request.
+    pub fn size(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Abort the process like the standard collections do.
+    pub(crate) fn abort(&self) -> ! {
+        stdalloc::handle_alloc_error(self.layout)
+    }
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "shsc: out of memory allocating {} bytes",
+            self.layout.size()
+        )
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// The global allocator.
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        stdalloc::alloc(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        stdalloc::dealloc(ptr, layout)
+    }
+
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, newsz: usize) -> *mut u8 {
+        stdalloc::realloc(ptr, layout, newsz)
+    }
+}
+
+/// The allocator owned by a list, None standing for Global.
+pub(crate) type Heap = Option<Rc<dyn Allocator>>;
+
+thread_local! {
+    static CURRENT: RefCell<Heap> = const { RefCell::new(None) };
+}
+
+/// Run f with heap as the allocator for the lists it creates on this thread.
+/// ### Arguments
+/// * `heap` - The allocator to use
+/// * `f` - The function to run
+/// ### Returns
+/// The result of f
+/// ### Example
+/// ```
+/// use std::rc::Rc;
+/// use shsc::util::alloc::{self, Pool};
+/// let pool = Rc::new(Pool::new());
+/// alloc::with_allocator(pool.clone(), || {
+///     let list = shsc::List::from(vec![shsc::todata!(1)]);
+///     drop(list);
+/// });
+/// assert!(pool.cached() > 0);
+/// ```
+
+pub fn with_allocator<R>(heap: Rc<dyn Allocator>, f: impl FnOnce() -> R) -> R {
+    scoped(Some(heap), f)
+}
+
+/// Run f with the global allocator as the allocator for the lists it creates.
+pub(crate) fn with_global<R>(f: impl FnOnce() -> R) -> R {
+    scoped(None, f)
+}
+
+fn scoped<R>(heap: Heap, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT.with(|current| current.replace(heap));
+    let result = f();
+    CURRENT.with(|current| current.replace(previous));
+    result
+}
+
+/// The allocator for lists created now on this thread.
+pub(crate) fn current() -> Heap {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Resolve a heap to its allocator.
+pub(crate) fn resolve(heap: &Heap) -> &dyn Allocator {
+    match heap {
+        Some(heap) => heap.as_ref(),
+        None => &Global,
+    }
+}
+
+pub fn allocate<T>(size: usize) -> *mut T {
+    allocate_in::<T>(&Global, size)
+}
+
+pub fn reallocate<T>(ptr: *mut T, oldsz: usize, newsz: usize) -> *mut T {
+    reallocate_in::<T>(&Global, ptr, oldsz, newsz)
+}
+
+pub fn deallocate<T>(ptr: *mut T, size: usize) {
+    deallocate_in::<T>(&Global, ptr, size)
+}
+
+pub fn allocate_in<T>(heap: &dyn Allocator, size: usize) -> *mut T {
+    try_allocate_in::<T>(heap, size).unwrap_or_else(|err| err.abort())
+}
+
+pub fn reallocate_in<T>(heap: &dyn Allocator, ptr: *mut T, oldsz: usize, newsz: usize) -> *mut T {
+    try_reallocate_in::<T>(heap, ptr, oldsz, newsz).unwrap_or_else(|err| err.abort())
+}
+
+pub fn try_allocate_in<T>(heap: &dyn Allocator, size: usize) -> Result<*mut T, AllocError> {
+    let layout = Layout::array::<T>(size).expect("allocate: Invalid layout");
+    let ptr = unsafe { heap.allocate(layout) };
+    if ptr.is_null() {
+        return Err(AllocError { layout });
+    }
+    Ok(ptr as *mut T)
+}
+
+/// On failure the old memory is left untouched.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn try_reallocate_in<T>(
+    heap: &dyn Allocator,
+    ptr: *mut T,
+    oldsz: usize,
+    newsz: usize,
+) -> Result<*mut T, AllocError> {
+    if ptr.is_null() {
+        return try_allocate_in::<T>(heap, newsz);
+    }
+    let old_layout = Layout::array::<T>(oldsz).expect("reallocate: Invalid layout");
+    let new_layout = Layout::array::<T>(newsz).expect("reallocate: Invalid layout");
+    let new_ptr = unsafe { heap.reallocate(ptr as *mut u8, old_layout, new_layout.size()) };
+    if new_ptr.is_null() {
+        return Err(AllocError { layout: new_layout });
+    }
+    Ok(new_ptr as *mut T)
+}
+
+/// Charge size bytes held elsewhere to an allocator.
+pub(crate) fn try_charge(heap: &dyn Allocator, size: usize) -> Result<(), AllocError> {
+    if size == 0 || heap.charge(size) {
+        return Ok(());
+    }
+    Err(AllocError::of_size(size))
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn deallocate_in<T>(heap: &dyn Allocator, ptr: *mut T, size: usize) {
+    let layout = Layout::array::<T>(size).expect("deallocate: Invalid layout");
+    unsafe {
+        heap.deallocate(ptr as *mut u8, layout);
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Allocator, Global};
+use std::alloc::Layout;
+use std::cell::RefCell;
+
+const MIN_CLASS: usize = 16;
+const MAX_CLASS: usize = 4096;
+const CLASSES: usize = 9;
+const BLOCK_ALIGN: usize = 16;
+
+/// Size-class pool allocator.
+/// Requests up to 4 KiB are rounded up to a power of two, and freed blocks are
+/// kept on a free list per size class for the next request of that class.
+/// Larger or over-aligned requests go to the global allocator.
+/// Cached blocks are released when the pool is dropped.
+pub struct Pool {
+    free: RefCell<[Vec<*mut u8>; CLASSES]>,
+}
+
+impl Pool {
+    /// Create a new empty pool.
+    /// ### Example
+    /// ```
+    /// let pool = shsc::util::alloc::Pool::new();
+    /// assert_eq!(pool.cached(), 0);
+    /// ```
+
+    pub fn new() -> Self {
+        Pool {
+            free: RefCell::new(Default::default()),
+        }
+    }
+
+    /// Get the number of freed blocks waiting for reuse.
+    /// ### Example
+    /// ```
+    /// use shsc::util::alloc::{self, Pool};
+    /// let pool = Pool::new();
+    /// let ptr = alloc::allocate_in::<u8>(&pool, 100);
+    /// alloc::deallocate_in::<u8>(&pool, ptr, 100);
+    /// assert_eq!(pool.cached(), 1);
+    /// let again = alloc::allocate_in::<u8>(&pool, 120);
+    /// assert_eq!(again, ptr);
+    /// assert_eq!(pool.cached(), 0);
+    /// alloc::deallocate_in::<u8>(&pool, again, 120);
+    /// ```
+
+    pub fn cached(&self) -> usize {
+        self.free.borrow().iter().map(|blocks| blocks.len()).sum()
+    }
+
+    fn class(size: usize, align: usize) -> Option<usize> {
+        if size > MAX_CLASS || align > BLOCK_ALIGN {
+            return None;
+        }
+        let size = size.max(MIN_CLASS).next_power_of_two();
+        Some((size.trailing_zeros() - MIN_CLASS.trailing_zeros()) as usize)
+    }
+
+    fn block_layout(class: usize) -> Layout {
+        Layout::from_size_align(MIN_CLASS << class, BLOCK_ALIGN)
+            .expect("shsc::Pool: invalid layout")
+    }
+}
+
+unsafe impl Allocator for Pool {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        match Pool::class(layout.size(), layout.align()) {
+            Some(class) => match self.free.borrow_mut()[class].pop() {
+                Some(block) => block,
+                None => Global.allocate(Pool::block_layout(class)),
+            },
+            None => Global.allocate(layout),
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        match Pool::class(layout.size(), layout.align()) {
+            Some(class) => self.free.borrow_mut()[class].push(ptr),
+            None => Global.deallocate(ptr, layout),
+        }
+    }
+
+    unsafe fn reallocate(&self, ptr: *mut u8, layout: Layout, newsz: usize) -> *mut u8 {
+        let old = Pool::class(layout.size(), layout.align());
+        if old.is_some() && old == Pool::class(newsz, layout.align()) {
+            return ptr;
+        }
+        if old.is_none() && Pool::class(newsz, layout.align()).is_none() {
+            return Global.reallocate(ptr, layout, newsz);
+        }
+        let new_ptr = self.allocate(Layout::from_size_align_unchecked(newsz, layout.align()));
+        if !new_ptr.is_null() {
+            std::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(newsz));
+            self.deallocate(ptr, layout);
+        }
+        new_ptr
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        for (class, blocks) in self.free.borrow().iter().enumerate() {
+            for &block in blocks {
+                unsafe { Global.deallocate(block, Pool::block_layout(class)) };
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// A weak reference to a heap container, stored in shsc::DataTypes::WEAK.
+/// Weak links do not keep their target alive, so they can be used for back-links without leaking cycles.
+pub enum Weak {
+    LIST(crate::list::Weak),
+    MAP(crate::map::Weak),
+}
+
+impl Weak {
+    /// Get a strong reference to the target if it is still alive
+    /// ### Returns
+    /// The target wrapped in shsc::Data, or None if it has been deallocated
+    /// ### Example
+    /// ```
+    /// let list = shsc::List::from(vec![shsc::todata!(1)]);
+    /// let data = shsc::todata!(list.downgrade());
+    /// let weak = data.as_weak().unwrap();
+    /// assert_eq!(weak.upgrade().unwrap().as_list().unwrap().len(), 1);
+    /// drop(list);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+
+    pub fn upgrade(&self) -> Option<crate::Data> {
+        match self {
+            Weak::LIST(weak) => weak.upgrade().map(crate::Data::from),
+            Weak::MAP(weak) => weak.upgrade().map(crate::Data::from),
+        }
+    }
+
+    /// Get the number of strong references to the target
+    /// ### Returns
+    /// The strong reference count, zero once the target has been deallocated
+    /// ### Example
+    /// ```
+    /// let m = shsc::Map::new();
+    /// let data = shsc::todata!(m.downgrade());
+    /// assert_eq!(data.as_weak().unwrap().strong_count(), 1);
+    /// ```
+
+    pub fn strong_count(&self) -> i64 {
+        match self {
+            Weak::LIST(weak) => weak.strong_count(),
+            Weak::MAP(weak) => weak.strong_count(),
+        }
+    }
+
+    fn addr(&self) -> usize {
+        match self {
+            Weak::LIST(weak) => weak.addr(),
+            Weak::MAP(weak) => weak.addr(),
+        }
+    }
+}
+
+impl crate::traits::ToStr for Weak {
+    /// Implement the ToStr trait for the Weak enum.
+    /// The target is not printed, so cycles through weak links terminate.
+    /// ### Example
+    /// ```
+    /// use shsc::traits::ToStr;
+    /// let list = shsc::List::new();
+    /// assert_eq!(shsc::todata!(list.downgrade()).tostr(), "<weak LIST>");
+    /// ```
+
+    fn tostr(&self) -> std::string::String {
+        match self {
+            Weak::LIST(_) => std::string::String::from("<weak LIST>"),
+            Weak::MAP(_) => std::string::String::from("<weak MAP>"),
+        }
+    }
+}
+
+impl Clone for Weak {
+    /// Implement the Clone trait for the Weak enum.
+    /// Results in another weak handle to the same target.
+    fn clone(&self) -> Self {
+        match self {
+            Weak::LIST(weak) => Weak::LIST(weak.clone()),
+            Weak::MAP(weak) => Weak::MAP(weak.clone()),
+        }
+    }
+}
+
+impl PartialEq for Weak {
+    /// Implement the PartialEq trait for the Weak enum.
+    /// Weak handles are equal when they point to the same target.
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl Eq for Weak {}
+
+impl PartialOrd for Weak {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Weak {
+    /// Implement the Ord trait for the Weak enum.
+    /// Weak handles are ordered by the address of their target.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.addr().cmp(&other.addr())
+    }
+}
+
+impl Hash for Weak {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state);
+    }
+}